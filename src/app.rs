@@ -1,28 +1,193 @@
 pub mod logic;
 pub mod ui;
 
-use logic::triangulation::TriangulationState;
+use egui::{Pos2, emath::TSTransform};
+use logic::{generators::GeneratorSettings, polygon::StyleSettings, triangulation::TriangulationState};
 
 // --------------------------------------------------
 // Базовое определение приложения
 // --------------------------------------------------
 
+/// Инструмент для работы с холстом.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Instrument {
+    /// Добавление точек триангуляции.
+    #[default]
+    AddPoint,
+    /// Перемещение существующих точек.
+    MoveVertex,
+    /// Построение окружности по трём точкам.
+    Circumcircle,
+    /// Вставка точки на ближайшее ребро триангуляции.
+    InsertVertex,
+    /// Удаление ближайшей точки.
+    DeleteVertex,
+    /// Задание веса ближайшей точки (для взвешенной триангуляции).
+    SetWeight,
+}
+
+impl std::fmt::Display for Instrument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instrument::AddPoint => write!(f, "Добавить точку"),
+            Instrument::MoveVertex => write!(f, "Переместить точку"),
+            Instrument::Circumcircle => write!(f, "Окружность по 3 точкам"),
+            Instrument::InsertVertex => write!(f, "Вставить точку на ребро"),
+            Instrument::DeleteVertex => write!(f, "Удалить точку"),
+            Instrument::SetWeight => write!(f, "Задать вес точки"),
+        }
+    }
+}
+
 /// Приложение-демонстрация аффинных преобразований.
-#[derive(Default)]
+///
+/// Состояние сохраняется между запусками через хранилище eframe,
+/// временные данные инструментов и интерфейса не сохраняются.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct AthenianApp {
     /// Состояние триангуляции.
     state: TriangulationState,
 
-    // Размеры холста.
+    /// Преобразование вида (панорамирование и масштаб): из мировых координат в экранные.
+    view: TSTransform,
+
+    /// Шаг сетки привязки (`None` — привязка выключена).
+    grid_size: Option<f32>,
+
+    /// Текущий инструмент.
+    instrument: Instrument,
+    /// Индекс точки, перемещаемой инструментом "Переместить точку".
+    #[serde(skip)]
+    grabbed_point: Option<usize>,
+    /// Точки, выбранные инструментом "Окружность по 3 точкам".
+    #[serde(skip)]
+    circle_points: Vec<Pos2>,
+    /// Окружность (центр, радиус), построенная по выбранным точкам.
+    #[serde(skip)]
+    tool_circle: Option<(Pos2, f32)>,
+    /// Вес, назначаемый инструментом "Задать вес точки" (квадрат радиуса диска).
+    point_weight: f32,
+
+    /// Количество шагов триангуляции за одно нажатие.
+    steps_per_click: usize,
+    /// Запущена ли автоматическая пошаговая анимация триангуляции.
+    #[serde(skip)]
+    running: bool,
+    /// Скорость анимации (шагов в секунду).
+    steps_per_second: f32,
+    /// Время последнего шага анимации.
+    #[serde(skip)]
+    last_step_time: Option<f64>,
+    /// Минимальный угол треугольников (в градусах) при улучшении сетки.
+    refine_min_angle: f32,
+    /// Максимальное количество точек (`None` — без ограничений).
+    max_points: Option<usize>,
+    /// Пользовательские стили рисования.
+    styles: StyleSettings,
+    /// Показано ли окно настройки стилей.
+    #[serde(skip)]
+    show_style_window: bool,
+    /// Заливать ли построенные треугольники.
+    fill_triangles: bool,
+    /// Выделять ли нарушения свойства пустой окружности.
+    show_violations: bool,
+    /// Рисовать ли диаграмму Вороного.
+    show_voronoi: bool,
+    /// Рисовать ли выпуклую оболочку точек.
+    show_hull: bool,
+    /// Параметр рисуемой альфа-формы (`None` — не рисуется).
+    alpha_shape: Option<f32>,
+    /// Параметры генерации точек.
+    generator: GeneratorSettings,
+    /// Показано ли окно генерации точек.
+    #[serde(skip)]
+    show_generator_window: bool,
+    /// Всплывающее предупреждение.
+    #[serde(skip)]
+    toast: Option<ui::Toast>,
+
+    // Размеры и положение холста на экране.
+    #[serde(skip)]
+    canvas_rect: egui::Rect,
+    #[serde(skip)]
     painter_width: f32,
+    #[serde(skip)]
     painter_height: f32,
 }
 
+impl Default for AthenianApp {
+    fn default() -> Self {
+        Self {
+            state: TriangulationState::default(),
+            view: TSTransform::IDENTITY,
+            grid_size: None,
+            instrument: Instrument::default(),
+            grabbed_point: None,
+            circle_points: Vec::new(),
+            tool_circle: None,
+            point_weight: 400.0,
+            steps_per_click: 1,
+            running: false,
+            steps_per_second: 5.0,
+            last_step_time: None,
+            refine_min_angle: 20.0,
+            max_points: None,
+            styles: StyleSettings::default(),
+            show_style_window: false,
+            fill_triangles: false,
+            show_violations: false,
+            show_voronoi: false,
+            show_hull: true,
+            alpha_shape: None,
+            generator: GeneratorSettings::default(),
+            show_generator_window: false,
+            toast: None,
+            canvas_rect: egui::Rect::NOTHING,
+            painter_width: 0.0,
+            painter_height: 0.0,
+        }
+    }
+}
+
 impl AthenianApp {
     /// Инициализация приложения.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // белая тема
         cc.egui_ctx.set_theme(egui::Theme::Light);
+
+        // восстановление состояния прошлого запуска
+        if let Some(storage) = cc.storage {
+            let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            app.sanitize();
+            return app;
+        }
+
         Self::default()
     }
+
+    /// Привести загруженное состояние к допустимому: настройки ограничиваются диапазонами
+    /// элементов управления, триангуляция с некорректными индексами сбрасывается.
+    /// Возвращает, была ли сброшена триангуляция.
+    fn sanitize(&mut self) -> bool {
+        self.grid_size = self
+            .grid_size
+            .filter(|grid_size| grid_size.is_finite() && *grid_size > 0.0)
+            .map(|grid_size| grid_size.clamp(5.0, 100.0));
+        self.steps_per_click = self.steps_per_click.clamp(1, 1000);
+        self.steps_per_second = if self.steps_per_second.is_finite() {
+            self.steps_per_second.clamp(0.5, 60.0)
+        } else {
+            Self::default().steps_per_second
+        };
+        self.refine_min_angle = if self.refine_min_angle.is_finite() {
+            self.refine_min_angle.clamp(0.0, 30.0)
+        } else {
+            Self::default().refine_min_angle
+        };
+        if !(self.view.scaling.is_finite() && self.view.scaling > 0.0 && self.view.translation.is_finite()) {
+            self.view = TSTransform::IDENTITY;
+        }
+        self.state.sanitize()
+    }
 }
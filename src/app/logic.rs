@@ -1,12 +1,34 @@
 use crate::app::{
-    AthenianApp,
-    logic::triangulation::{TriangulationState, init_triangulation, step_triangulation},
+    AthenianApp, Instrument,
+    logic::{
+        generators::generate_points,
+        polygon::PolygonStyle,
+        triangulation::{circumcircle, complete_triangulation, init_triangulation, step_triangulation},
+    },
 };
-use egui::{Color32, Painter, Response, Ui};
+use egui::{Color32, Painter, Response, Ui, emath::TSTransform};
 
+pub mod bowyer_watson;
+pub mod generators;
+pub mod geometry;
 pub mod polygon;
+pub mod predicates;
+pub mod spatial_index;
 pub mod triangulation;
 
+/// Максимальное расстояние от курсора до точки, при котором её можно захватить.
+const GRAB_DISTANCE: f32 = 15.0;
+/// Минимальный экранный шаг сетки, при котором она рисуется.
+const MIN_GRID_SCREEN_STEP: f32 = 4.0;
+/// Изменение шага сетки привязки клавишами +/-.
+const GRID_SIZE_STEP: f32 = 5.0;
+/// Допустимый шаг сетки привязки (как у ползунка в панели).
+const GRID_SIZE_RANGE: std::ops::RangeInclusive<f32> = 5.0..=100.0;
+/// Изменение веса инструмента "Задать вес точки" клавишами +/-.
+const POINT_WEIGHT_STEP: f32 = 100.0;
+/// Скорость масштабирования колесом мыши.
+const ZOOM_SPEED: f32 = 0.002;
+
 // --------------------------------------------------
 // Обработка области рисования (холст)
 // --------------------------------------------------
@@ -22,6 +44,7 @@ impl AthenianApp {
             egui::Vec2::new(self.painter_width, self.painter_height),
             egui::Sense::click_and_drag(),
         );
+        self.canvas_rect = response.rect;
 
         // цвет холста
         painter.rect_filled(response.rect, 0.0, Color32::WHITE);
@@ -38,12 +61,215 @@ impl AthenianApp {
 
     /// Очистить холст.
     pub fn clear_canvas(&mut self) {
-        self.state = TriangulationState::default();
+        self.state.clear_points();
+        self.grabbed_point = None;
+        self.circle_points.clear();
+        self.tool_circle = None;
+    }
+
+    /// Объединить точки, которые на экране ближе радиуса вершины друг к другу,
+    /// и сообщить, сколько точек удалено. Анимация при этом останавливается.
+    pub fn merge_close_points(&mut self) {
+        self.pause_animation();
+        let tolerance = self.styles.standard.vertex_radius / self.view.scaling;
+        let removed = self.state.merge_close_points(tolerance);
+        self.grabbed_point = None;
+        self.show_warning(format!("Объединено близких точек: удалено {removed}"));
     }
 
     /// Нарисовать холст.
     pub fn draw_canvas(&mut self, painter: &Painter) {
-        self.state.draw(painter);
+        self.draw_grid(painter);
+        self.state.draw(painter, &self.view, &self.styles, self.fill_triangles);
+        if self.show_violations {
+            self.state.draw_delaunay_violations(painter, &self.view);
+        }
+        if self.show_voronoi {
+            self.state.draw_voronoi(painter, &self.view);
+        }
+        if self.show_hull {
+            self.state.draw_convex_hull(painter, &self.view);
+        }
+        if let Some(alpha) = self.alpha_shape {
+            self.state.draw_alpha_shape(painter, &self.view, alpha);
+        }
+        self.draw_circle_tool(painter);
+    }
+
+    /// Нарисовать линии сетки привязки в видимой части холста.
+    fn draw_grid(&self, painter: &Painter) {
+        let Some(grid_size) = self.grid_size else {
+            return;
+        };
+        // слишком частая сетка сливается в сплошную заливку
+        if grid_size * self.view.scaling < MIN_GRID_SCREEN_STEP {
+            return;
+        }
+
+        let screen_rect = painter.clip_rect();
+        let world_rect = self.view.inverse() * screen_rect;
+        let stroke = egui::epaint::Stroke::new(1.0, Color32::from_gray(230));
+
+        let mut x = (world_rect.min.x / grid_size).ceil() * grid_size;
+        while x <= world_rect.max.x {
+            let screen_x = (self.view * egui::Pos2::new(x, 0.0)).x;
+            painter.vline(screen_x, screen_rect.y_range(), stroke);
+            x += grid_size;
+        }
+        let mut y = (world_rect.min.y / grid_size).ceil() * grid_size;
+        while y <= world_rect.max.y {
+            let screen_y = (self.view * egui::Pos2::new(0.0, y)).y;
+            painter.hline(screen_rect.x_range(), screen_y, stroke);
+            y += grid_size;
+        }
+    }
+
+    /// Нарисовать точки и окружность инструмента "Окружность по 3 точкам".
+    fn draw_circle_tool(&self, painter: &Painter) {
+        let style = PolygonStyle::current();
+        for point in &self.circle_points {
+            painter.circle_filled(self.view * *point, style.vertex_radius, style.vertex_color);
+        }
+        if let Some((center, radius)) = self.tool_circle {
+            let center = self.view * center;
+            painter.circle_stroke(
+                center,
+                radius * self.view.scaling,
+                egui::epaint::Stroke::new(style.arrow_width, style.edge_color),
+            );
+            painter.circle_filled(center, style.intersection_radius, style.intersection_color);
+        }
+    }
+}
+
+// --------------------------------------------------
+// Статистика сцены
+// --------------------------------------------------
+
+/// Сводные характеристики сцены.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SceneStats {
+    /// Количество точек.
+    pub point_count: usize,
+    /// Количество построенных треугольников.
+    pub triangle_count: usize,
+    /// Суммарная площадь треугольников.
+    pub total_area: f32,
+}
+
+impl AthenianApp {
+    /// Собрать сводные характеристики сцены.
+    pub fn scene_stats(&self) -> SceneStats {
+        SceneStats {
+            point_count: self.state.points.len(),
+            triangle_count: self.state.triangles.len(),
+            total_area: self
+                .state
+                .triangles
+                .iter()
+                .map(|triangle| triangle.area(&self.state.points))
+                .sum(),
+        }
+    }
+}
+
+// --------------------------------------------------
+// Импорт и экспорт
+// --------------------------------------------------
+
+impl AthenianApp {
+    /// Сохранить точки триангуляции в CSV-файл, выбранный пользователем.
+    pub fn export_points_csv(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("points.csv")
+            .save_file()
+        else {
+            return;
+        };
+
+        if let Err(err) = std::fs::write(&path, self.state.points_to_csv(true)) {
+            self.show_warning(format!("Не удалось сохранить точки: {err}"));
+        }
+    }
+
+    /// Сгенерировать точки по параметрам `generator` в видимой части холста.
+    /// Построенная триангуляция сбрасывается.
+    pub fn generate_points(&mut self) {
+        if !self.canvas_rect.is_positive() {
+            return;
+        }
+        let area = self.view.inverse() * self.canvas_rect;
+        let mut points = generate_points(&self.generator, area);
+
+        if self.generator.replace {
+            self.clear_canvas();
+        }
+        if let Some(max_points) = self.max_points {
+            let free = max_points.saturating_sub(self.state.points.len());
+            if points.len() > free {
+                points.truncate(free);
+                self.show_warning("Достигнуто максимальное количество точек");
+            }
+        }
+        self.state.points.extend(points);
+        self.state.reset();
+    }
+
+    /// Сохранить сцену в SVG-файл, выбранный пользователем.
+    pub fn export_svg(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("SVG", &["svg"])
+            .set_file_name("triangulation.svg")
+            .save_file()
+        else {
+            return;
+        };
+
+        if let Err(err) = std::fs::write(&path, self.state.to_svg(&self.styles, self.fill_triangles)) {
+            self.show_warning(format!("Не удалось сохранить SVG: {err}"));
+        }
+    }
+
+    /// Сохранить сцену (точки, триангуляцию, вид и настройки) в JSON-файл проекта.
+    pub fn save_project(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Проект", &["json"])
+            .set_file_name("scene.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            self.show_warning(format!("Не удалось сохранить проект: {err}"));
+        }
+    }
+
+    /// Загрузить сцену из JSON-файла проекта, заменив текущую.
+    pub fn open_project(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Проект", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| serde_json::from_str::<AthenianApp>(&json).map_err(|err| err.to_string()));
+        match result {
+            Ok(mut app) => {
+                if app.sanitize() {
+                    app.show_warning("Триангуляция в проекте повреждена и сброшена");
+                }
+                *self = app;
+            }
+            Err(err) => self.show_warning(format!("Не удалось открыть проект: {err}")),
+        }
     }
 }
 
@@ -52,16 +278,281 @@ impl AthenianApp {
 // --------------------------------------------------
 
 impl AthenianApp {
+    /// Сменить инструмент.
+    ///
+    /// Точки и триангуляция при смене сохраняются; сбрасываются только временные данные
+    /// инструментов: захваченная точка и точки/окружность инструмента "Окружность по 3 точкам".
+    pub fn set_instrument(&mut self, instrument: Instrument) {
+        if self.instrument == instrument {
+            return;
+        }
+        self.instrument = instrument;
+        self.grabbed_point = None;
+        self.circle_points.clear();
+        self.tool_circle = None;
+    }
+
     /// Обработать взаимодействие с холстом.
     pub fn handle_input(&mut self, response: &Response) {
+        self.handle_view(response);
         self.handle_click(response);
+        self.handle_drag(response);
+        self.handle_keys(&response.ctx);
+    }
+
+    /// Обработать нажатия клавиш: Backspace удаляет последнюю добавленную точку,
+    /// `+`/`-` меняют основной параметр текущего инструмента.
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        // не перехватывать ввод в текстовых полях
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        if self.instrument == Instrument::AddPoint && ctx.input(|i| i.key_pressed(egui::Key::Backspace)) {
+            self.state.remove_last_point();
+        }
+
+        let (increase, decrease) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::Plus) || i.key_pressed(egui::Key::Equals),
+                i.key_pressed(egui::Key::Minus),
+            )
+        });
+        match (increase, decrease) {
+            (true, false) => self.adjust_primary_parameter(1),
+            (false, true) => self.adjust_primary_parameter(-1),
+            _ => {}
+        }
+    }
+
+    /// Изменить основной параметр текущего инструмента на `delta` шагов: при включённой
+    /// привязке — шаг сетки, у инструмента "Задать вес точки" — назначаемый вес,
+    /// иначе — число шагов триангуляции за нажатие.
+    pub fn adjust_primary_parameter(&mut self, delta: i32) {
+        if let Some(grid_size) = &mut self.grid_size {
+            *grid_size = (*grid_size + GRID_SIZE_STEP * delta as f32)
+                .clamp(*GRID_SIZE_RANGE.start(), *GRID_SIZE_RANGE.end());
+            return;
+        }
+
+        match self.instrument {
+            Instrument::SetWeight => {
+                self.point_weight = (self.point_weight + POINT_WEIGHT_STEP * delta as f32).clamp(0.0, 10000.0);
+            }
+            // у остальных инструментов своих числовых параметров нет — меняется число шагов
+            _ => {
+                self.steps_per_click = self.steps_per_click.saturating_add_signed(delta as isize).clamp(1, 1000);
+            }
+        }
     }
 
     /// Обработать клики по холсту.
     fn handle_click(&mut self, response: &Response) {
         if response.clicked_by(egui::PointerButton::Primary) {
-            let pos = response.hover_pos().unwrap();
-            self.state.points.push(pos);
+            // при сенсорном вводе или уходе курсора с холста позиции может не быть
+            let Some(pos) = response.hover_pos() else {
+                return;
+            };
+            let pos = self.to_world(pos);
+            match self.instrument {
+                Instrument::AddPoint => self.add_point(self.snap_to_grid(pos)),
+                Instrument::MoveVertex => {}
+                Instrument::Circumcircle => self.add_circle_point(self.snap_to_grid(pos)),
+                Instrument::InsertVertex => self.insert_point_on_edge(pos),
+                Instrument::DeleteVertex => self.delete_point(pos),
+                Instrument::SetWeight => self.set_point_weight(pos),
+            }
+        }
+    }
+
+    /// Привязать точку (в мировых координатах) к ближайшему узлу сетки, если привязка включена.
+    pub fn snap_to_grid(&self, pos: egui::Pos2) -> egui::Pos2 {
+        match self.grid_size {
+            Some(grid_size) => (pos / grid_size).round() * grid_size,
+            None => pos,
+        }
+    }
+
+    /// Перевести экранные координаты в мировые.
+    pub fn to_world(&self, screen_pos: egui::Pos2) -> egui::Pos2 {
+        self.view.inverse() * screen_pos
+    }
+
+    /// Обработать масштабирование колесом мыши (относительно курсора)
+    /// и панорамирование перетаскиванием средней кнопкой.
+    fn handle_view(&mut self, response: &Response) {
+        if response.dragged_by(egui::PointerButton::Middle) {
+            self.view = TSTransform::from_translation(response.drag_delta()) * self.view;
+        }
+
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        let scroll = response.ctx.input(|i| i.smooth_scroll_delta.y);
+        if scroll != 0.0 {
+            let zoom = (scroll * ZOOM_SPEED).exp();
+            self.view = TSTransform::from_translation(pointer.to_vec2())
+                * TSTransform::from_scaling(zoom)
+                * TSTransform::from_translation(-pointer.to_vec2())
+                * self.view;
+        }
+    }
+
+    /// Сбросить панорамирование и масштаб.
+    pub fn reset_view(&mut self) {
+        self.view = TSTransform::IDENTITY;
+    }
+
+    /// Обработать перетаскивание по холсту.
+    fn handle_drag(&mut self, response: &Response) {
+        if self.instrument != Instrument::MoveVertex {
+            return;
+        }
+
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            self.grabbed_point = response.interact_pointer_pos().and_then(|pos| {
+                self.state
+                    .nearest_point(self.to_world(pos), GRAB_DISTANCE / self.view.scaling)
+            });
+        }
+
+        if response.dragged_by(egui::PointerButton::Primary)
+            && let (Some(index), Some(pos)) = (self.grabbed_point, response.interact_pointer_pos())
+        {
+            self.state.move_point(index, self.snap_to_grid(self.to_world(pos)));
+        }
+
+        if response.drag_stopped_by(egui::PointerButton::Primary) {
+            self.grabbed_point = None;
+        }
+    }
+
+    /// Добавить точку триангуляции.
+    ///
+    /// Клик в пределах радиуса вершины от существующей точки игнорируется: совпадающие точки
+    /// ломают поиск начального ребра.
+    fn add_point(&mut self, pos: egui::Pos2) {
+        let radius = self.styles.standard.vertex_radius / self.view.scaling;
+        if self.state.nearest_point(pos, radius).is_some() {
+            return;
+        }
+        if self
+            .max_points
+            .is_some_and(|max_points| self.state.points.len() >= max_points)
+        {
+            self.show_warning("Достигнуто максимальное количество точек");
+            return;
+        }
+        self.state.points.push(pos);
+    }
+
+    /// Вставить точку на ближайшее к `pos` ребро триангуляции (в пределах `GRAB_DISTANCE` пикселей).
+    /// Ребро разбивается, поэтому построенная триангуляция сбрасывается.
+    fn insert_point_on_edge(&mut self, pos: egui::Pos2) {
+        let Some((_, projection)) = self.state.nearest_edge(pos, GRAB_DISTANCE / self.view.scaling) else {
+            return;
+        };
+        let point_count = self.state.points.len();
+        self.add_point(projection);
+        if self.state.points.len() != point_count {
+            self.state.reset();
+        }
+    }
+
+    /// Удалить ближайшую к `pos` точку (в пределах `GRAB_DISTANCE` пикселей).
+    fn delete_point(&mut self, pos: egui::Pos2) {
+        if let Some(index) = self.state.nearest_point(pos, GRAB_DISTANCE / self.view.scaling) {
+            self.state.remove_point(index);
+        }
+    }
+
+    /// Назначить ближайшей к `pos` точке (в пределах `GRAB_DISTANCE` пикселей) вес `point_weight`.
+    fn set_point_weight(&mut self, pos: egui::Pos2) {
+        if let Some(index) = self.state.nearest_point(pos, GRAB_DISTANCE / self.view.scaling) {
+            self.state.set_weight(index, self.point_weight);
+        }
+    }
+
+    /// Добавить точку для построения окружности. Третья точка строит окружность,
+    /// следующая начинает новый набор.
+    fn add_circle_point(&mut self, pos: egui::Pos2) {
+        if self.circle_points.len() == 3 {
+            self.circle_points.clear();
+            self.tool_circle = None;
+        }
+        self.circle_points.push(pos);
+
+        if let [a, b, c] = self.circle_points[..] {
+            self.tool_circle = circumcircle(a, b, c);
+            if self.tool_circle.is_none() {
+                self.show_warning("Точки лежат на одной прямой — окружность не существует");
+            }
+        }
+    }
+
+    /// Выполнить `steps_per_click` шагов триангуляции (меньше, если она завершится раньше).
+    pub fn do_triangulation_steps(&mut self) {
+        for _ in 0..self.steps_per_click {
+            if self.state.is_triangulation_completed() {
+                break;
+            }
+            self.do_triangulation_step();
+        }
+    }
+
+    /// Сбросить триангуляцию, сохранив точки. Анимация при этом останавливается.
+    pub fn reset_triangulation(&mut self) {
+        self.pause_animation();
+        self.state.reset();
+    }
+
+    /// Улучшить сетку, добавив точки до минимального угла треугольников `refine_min_angle`.
+    /// Анимация при этом останавливается.
+    pub fn refine_mesh(&mut self) {
+        self.pause_animation();
+        self.state.refine(self.refine_min_angle, f32::INFINITY);
+    }
+
+    /// Запустить автоматическое выполнение шагов со скоростью `steps_per_second`.
+    pub fn run_animation(&mut self) {
+        self.running = true;
+        self.last_step_time = None;
+    }
+
+    /// Приостановить автоматическое выполнение шагов.
+    pub fn pause_animation(&mut self) {
+        self.running = false;
+        self.last_step_time = None;
+    }
+
+    /// Выполнить шаги анимации, накопившиеся с прошлого кадра (по `steps_per_click` за такт),
+    /// и запланировать перерисовку
+    /// к следующему шагу. После завершения триангуляции анимация останавливается.
+    pub fn advance_animation(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let interval = 1.0 / self.steps_per_second.max(f32::EPSILON) as f64;
+        // после долгого перерыва между кадрами догоняется не больше секунды
+        let last_step_time = self.last_step_time.unwrap_or(now - interval).max(now - 1.0 - interval);
+
+        let mut next_step_time = last_step_time + interval;
+        while next_step_time <= now && !self.state.is_triangulation_completed() {
+            self.do_triangulation_steps();
+            self.last_step_time = Some(next_step_time);
+            next_step_time += interval;
+        }
+
+        // триангуляция завершена либо не инициализирована из-за нехватки точек
+        if self.state.is_triangulation_completed() || !self.state.is_triangulation_initialized() {
+            self.pause_animation();
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64((next_step_time - now).max(0.0)));
+    }
+
+    /// Инициализировать триангуляцию (найти начальное ребро), если это ещё не сделано.
+    pub fn init_triangulation(&mut self) {
+        if !self.state.is_triangulation_initialized() {
+            init_triangulation(&mut self.state);
         }
     }
 
@@ -78,9 +569,347 @@ impl AthenianApp {
         step_triangulation(&mut self.state);
     }
 
+    /// Идёт ли анимация, требующая перерисовки холста по таймеру.
+    pub fn is_animation_active(&self) -> bool {
+        self.running
+    }
+
     pub fn do_full_triangulation(&mut self) {
-        while !self.state.is_triangulation_completed() {
-            self.do_triangulation_step();
+        if !self.state.is_triangulation_initialized() {
+            init_triangulation(&mut self.state);
         }
+        complete_triangulation(&mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::logic::polygon::Polygon;
+    use egui::Pos2;
+
+    /// Нажатие или отпускание основной кнопки мыши.
+    fn primary_button(pos: Pos2, pressed: bool) -> egui::Event {
+        egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        }
+    }
+
+    /// Прогнать кадр с холстом во всё окно и передать ввод приложению.
+    /// Возвращает, был ли в кадре клик и позицию курсора над холстом.
+    fn canvas_frame(
+        app: &mut AthenianApp,
+        ctx: &egui::Context,
+        time: f64,
+        events: Vec<egui::Event>,
+    ) -> (bool, Option<Pos2>) {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                Pos2::ZERO,
+                egui::Vec2::new(800.0, 600.0),
+            )),
+            time: Some(time),
+            events,
+            ..egui::RawInput::default()
+        };
+        let mut result = (false, None);
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let (response, _painter) = app.allocate_painter(ui);
+                app.handle_input(&response);
+                result = (
+                    response.clicked_by(egui::PointerButton::Primary),
+                    response.hover_pos(),
+                );
+            });
+        });
+        result
+    }
+
+    #[test]
+    fn add_point_beyond_max_points_shows_warning() {
+        let mut app = AthenianApp {
+            max_points: Some(3),
+            ..AthenianApp::default()
+        };
+        for x in [0.0, 100.0, 200.0] {
+            app.add_point(Pos2::new(x, 0.0));
+        }
+        assert_eq!(app.state.points.len(), 3);
+        assert!(app.toast.is_none());
+
+        app.add_point(Pos2::new(300.0, 0.0));
+        assert_eq!(app.state.points.len(), 3);
+        assert!(app.toast.is_some());
+    }
+
+    #[test]
+    fn click_without_hover_position_is_ignored() {
+        let mut app = AthenianApp::default();
+        let ctx = egui::Context::default();
+        let pos = Pos2::new(100.0, 100.0);
+
+        canvas_frame(&mut app, &ctx, 0.0, vec![egui::Event::PointerMoved(pos)]);
+        canvas_frame(&mut app, &ctx, 0.1, vec![primary_button(pos, true)]);
+        // кнопка отпущена, и указатель в том же кадре покинул окно (как при сенсорном вводе)
+        let (clicked, hover_pos) = canvas_frame(
+            &mut app,
+            &ctx,
+            0.2,
+            vec![primary_button(pos, false), egui::Event::PointerGone],
+        );
+        assert!(clicked);
+        assert_eq!(hover_pos, None);
+        assert!(app.state.points.is_empty());
+
+        // обычный клик по-прежнему добавляет точку
+        canvas_frame(
+            &mut app,
+            &ctx,
+            0.3,
+            vec![egui::Event::PointerMoved(pos), primary_button(pos, true)],
+        );
+        let (clicked, _) = canvas_frame(&mut app, &ctx, 0.4, vec![primary_button(pos, false)]);
+        assert!(clicked);
+        assert_eq!(app.state.points, vec![pos]);
+    }
+
+    #[test]
+    fn animation_is_active_only_during_playback() {
+        let mut app = AthenianApp::default();
+        app.state.points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(100.0, 10.0),
+            Pos2::new(40.0, 120.0),
+            Pos2::new(150.0, 90.0),
+            Pos2::new(70.0, 60.0),
+        ];
+        assert!(!app.is_animation_active());
+
+        app.run_animation();
+        assert!(app.is_animation_active());
+
+        // кадры раз в секунду, пока анимация не остановится сама
+        let ctx = egui::Context::default();
+        for frame in 0..100 {
+            let input = egui::RawInput {
+                time: Some(frame as f64),
+                ..egui::RawInput::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                if app.is_animation_active() {
+                    app.advance_animation(ctx);
+                }
+            });
+            if !app.is_animation_active() {
+                break;
+            }
+        }
+        assert!(app.state.is_triangulation_completed());
+        assert!(!app.is_animation_active());
+
+        app.reset_triangulation();
+        app.run_animation();
+        app.pause_animation();
+        assert!(!app.is_animation_active());
+    }
+
+    #[test]
+    fn scene_stats_aggregate_two_triangles() {
+        let mut app = AthenianApp::default();
+        // прямоугольник 4 x 3, разрезанный диагональю
+        app.state.points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(4.0, 3.0),
+            Pos2::new(0.0, 3.0),
+        ];
+        app.state.triangles = [Polygon::from_poses([0, 1, 2]), Polygon::from_poses([0, 2, 3])].into();
+
+        assert_eq!(
+            app.scene_stats(),
+            SceneStats {
+                point_count: 4,
+                triangle_count: 2,
+                total_area: 12.0,
+            }
+        );
+    }
+
+    /// Приложение с набором точек общего положения.
+    fn app_with_points() -> AthenianApp {
+        let mut app = AthenianApp::default();
+        app.state.points = (0..12)
+            .map(|i| Pos2::new((i * 37 % 101) as f32 * 5.0, (i * 53 % 97) as f32 * 5.0))
+            .collect();
+        app
+    }
+
+    #[test]
+    fn batch_steps_match_single_steps() {
+        let mut single = app_with_points();
+        for _ in 0..5 {
+            single.do_triangulation_step();
+        }
+
+        let mut batch = app_with_points();
+        batch.steps_per_click = 5;
+        batch.do_triangulation_steps();
+        assert_eq!(batch.state.triangles, single.state.triangles);
+        assert_eq!(batch.state.alive_edges, single.state.alive_edges);
+        assert_eq!(batch.state.current_edge, single.state.current_edge);
+
+        // один такт анимации выполняет столько же шагов, сколько одно нажатие
+        let mut animated = app_with_points();
+        animated.steps_per_click = 5;
+        animated.run_animation();
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            time: Some(0.0),
+            ..egui::RawInput::default()
+        };
+        let _ = ctx.run(input, |ctx| animated.advance_animation(ctx));
+        assert_eq!(animated.state.triangles, single.state.triangles);
+        assert_eq!(animated.state.alive_edges, single.state.alive_edges);
+    }
+
+    #[test]
+    fn merge_close_points_reports_removed_count() {
+        let mut app = AthenianApp::default();
+        // радиус вершины 7 пикселей при единичном масштабе
+        app.state.points = vec![Pos2::new(0.0, 0.0), Pos2::new(5.0, 0.0), Pos2::new(100.0, 0.0)];
+
+        app.merge_close_points();
+        assert_eq!(app.state.points, vec![Pos2::new(2.5, 0.0), Pos2::new(100.0, 0.0)]);
+        assert_eq!(
+            app.toast.map(|toast| toast.message),
+            Some("Объединено близких точек: удалено 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn circle_instrument_builds_circle_from_three_points() {
+        let mut app = AthenianApp::default();
+        for pos in [Pos2::new(0.0, 0.0), Pos2::new(6.0, 0.0), Pos2::new(0.0, 8.0)] {
+            app.add_circle_point(pos);
+        }
+        assert_eq!(app.tool_circle, Some((Pos2::new(3.0, 4.0), 5.0)));
+        assert!(app.toast.is_none());
+
+        // четвёртая точка начинает новый набор; точки на одной прямой дают предупреждение
+        for pos in [Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0), Pos2::new(2.0, 2.0)] {
+            app.add_circle_point(pos);
+        }
+        assert_eq!(app.circle_points.len(), 3);
+        assert_eq!(app.tool_circle, None);
+        assert!(app.toast.is_some());
+    }
+
+    #[test]
+    fn plus_minus_adjust_grid_size_while_snapping() {
+        let mut app = AthenianApp::default();
+        app.adjust_primary_parameter(1);
+        assert_eq!(app.steps_per_click, 2);
+        app.adjust_primary_parameter(-5);
+        assert_eq!(app.steps_per_click, 1);
+
+        // при включённой привязке меняется шаг сетки в пределах ползунка
+        app.grid_size = Some(20.0);
+        app.adjust_primary_parameter(1);
+        assert_eq!(app.grid_size, Some(25.0));
+        app.adjust_primary_parameter(-10);
+        assert_eq!(app.grid_size, Some(5.0));
+        app.adjust_primary_parameter(100);
+        assert_eq!(app.grid_size, Some(100.0));
+        assert_eq!(app.steps_per_click, 1);
+    }
+
+    #[test]
+    fn plus_minus_adjust_weight_under_set_weight() {
+        let mut app = AthenianApp::default();
+        app.set_instrument(Instrument::SetWeight);
+        app.adjust_primary_parameter(1);
+        assert_eq!(app.point_weight, 500.0);
+        app.adjust_primary_parameter(-10);
+        assert_eq!(app.point_weight, 0.0);
+        assert_eq!(app.steps_per_click, 1);
+    }
+
+    #[test]
+    fn set_instrument_clears_tool_state_but_keeps_triangulation() {
+        let mut app = app_with_points();
+        app.do_full_triangulation();
+        let triangles = app.state.triangles.clone();
+        assert!(!triangles.is_empty());
+
+        app.set_instrument(Instrument::Circumcircle);
+        for pos in [Pos2::new(0.0, 0.0), Pos2::new(6.0, 0.0), Pos2::new(0.0, 8.0)] {
+            app.add_circle_point(pos);
+        }
+        app.grabbed_point = Some(0);
+        assert!(app.tool_circle.is_some());
+
+        // повторный выбор того же инструмента ничего не сбрасывает
+        app.set_instrument(Instrument::Circumcircle);
+        assert_eq!(app.circle_points.len(), 3);
+
+        app.set_instrument(Instrument::MoveVertex);
+        assert_eq!(app.instrument, Instrument::MoveVertex);
+        assert_eq!(app.grabbed_point, None);
+        assert!(app.circle_points.is_empty());
+        assert_eq!(app.tool_circle, None);
+        assert_eq!(app.state.points.len(), 12);
+        assert_eq!(app.state.triangles, triangles);
+    }
+
+    #[test]
+    fn sanitize_repairs_crafted_project() {
+        let mut app = app_with_points();
+        app.do_full_triangulation();
+        let mut json = serde_json::to_value(&app).unwrap();
+        // треугольник и рёбра ссылаются на несуществующие точки, настройки вне диапазонов
+        json["state"]["points"] = serde_json::json!([{ "x": 0.0, "y": 0.0 }, { "x": 10.0, "y": 0.0 }]);
+        json["grid_size"] = serde_json::json!(-3.0);
+        json["steps_per_click"] = serde_json::json!(0);
+        json["steps_per_second"] = serde_json::json!(1000.0);
+        json["view"]["scaling"] = serde_json::json!(0.0);
+
+        let mut app: AthenianApp = serde_json::from_value(json).unwrap();
+        assert!(app.sanitize());
+        assert!(app.state.triangles.is_empty());
+        assert!(!app.state.is_triangulation_initialized());
+        assert_eq!(app.grid_size, None);
+        assert_eq!(app.steps_per_click, 1);
+        assert_eq!(app.steps_per_second, 60.0);
+        assert_eq!(app.view, TSTransform::IDENTITY);
+
+        // отрисовка и экспорт больше не обращаются к отсутствующим точкам
+        let style = PolygonStyle::dead();
+        assert!(app.state.triangle_fill_shapes(&app.view, &style).is_empty());
+        app.state.to_svg(&app.styles, true);
+
+        // корректное состояние не меняется
+        let mut app = app_with_points();
+        app.do_full_triangulation();
+        app.grid_size = Some(500.0);
+        let triangles = app.state.triangles.clone();
+        assert!(!app.sanitize());
+        assert_eq!(app.state.triangles, triangles);
+        assert_eq!(app.grid_size, Some(100.0));
+    }
+
+    #[test]
+    fn sanitize_resets_inconsistent_bowyer_watson_state() {
+        let mut app = app_with_points();
+        app.state.set_algorithm(crate::app::logic::triangulation::Algorithm::BowyerWatson);
+        app.do_triangulation_steps();
+        assert!(!app.sanitize());
+
+        app.state.bowyer_watson.triangles.push([0, 1, app.state.points.len() + 3]);
+        assert!(app.sanitize());
+        assert!(app.state.bowyer_watson.triangles.is_empty());
     }
 }
@@ -0,0 +1,244 @@
+use egui::Pos2;
+use std::collections::HashMap;
+
+use crate::app::logic::{
+    polygon::Polygon,
+    predicates::{incircle, orient2d},
+    triangulation::TriangulationState,
+};
+
+/// Во сколько раз супертреугольник больше ограничивающего прямоугольника точек.
+const SUPER_TRIANGLE_SCALE: f32 = 1000.0;
+
+// --------------------------------------------------
+// Состояние алгоритма Бойера — Уотсона
+// --------------------------------------------------
+
+/// Состояние пошагового алгоритма Бойера — Уотсона: точки вставляются по одной
+/// в триангуляцию, изначально состоящую из одного супертреугольника.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BowyerWatsonState {
+    /// Вершины супертреугольника, содержащего все точки.
+    pub super_triangle: Option<[Pos2; 3]>,
+    /// Текущие треугольники. Индексы от `points.len()` и выше обозначают вершины супертреугольника.
+    pub triangles: Vec<[usize; 3]>,
+    /// Количество точек на момент инициализации: от него отсчитываются индексы супертреугольника.
+    pub point_count: usize,
+    /// Количество уже вставленных точек.
+    pub inserted: usize,
+    /// Граница полости, перестроенной на последнем шаге.
+    pub cavity: Vec<(usize, usize)>,
+}
+
+impl BowyerWatsonState {
+    /// Координаты вершины с учётом вершин супертреугольника.
+    fn vertex(&self, points: &[Pos2], index: usize) -> Pos2 {
+        match index.checked_sub(points.len()) {
+            None => points[index],
+            Some(super_index) => self.super_triangle.expect("супертреугольник не построен")[super_index],
+        }
+    }
+}
+
+// --------------------------------------------------
+// Шаги алгоритма
+// --------------------------------------------------
+
+/// Инициализировать алгоритм: построить супертреугольник вокруг всех точек.
+pub fn init_bowyer_watson(state: &mut TriangulationState) {
+    if state.points.len() < 3 {
+        return;
+    }
+
+    let rect = egui::Rect::from_points(&state.points);
+    let size = rect.width().max(rect.height()).max(1.0) * SUPER_TRIANGLE_SCALE;
+    let center = rect.center();
+
+    let n = state.points.len();
+    state.triangles.clear();
+    state.bowyer_watson = BowyerWatsonState {
+        super_triangle: Some([
+            Pos2::new(center.x - size, center.y + size),
+            Pos2::new(center.x + size, center.y + size),
+            Pos2::new(center.x, center.y - size),
+        ]),
+        triangles: vec![[n, n + 1, n + 2]],
+        point_count: n,
+        inserted: 0,
+        cavity: Vec::new(),
+    };
+}
+
+/// Вставить следующую точку: удалить треугольники, в описанную окружность которых
+/// она попадает, и соединить точку с границей образовавшейся полости.
+///
+/// Если набор точек изменился после инициализации, построение начинается заново.
+pub fn step_bowyer_watson(state: &mut TriangulationState) {
+    let index = state.bowyer_watson.inserted;
+    if state.bowyer_watson.super_triangle.is_none() || index >= state.points.len() {
+        return;
+    }
+
+    let n = state.points.len();
+    if state.bowyer_watson.point_count != n {
+        init_bowyer_watson(state);
+        return;
+    }
+    let pos = state.points[index];
+
+    let bad_mask = cavity_mask(&state.bowyer_watson, &state.points, pos);
+    let (mut bad, mut good) = (Vec::new(), Vec::new());
+    for (triangle, is_bad) in state.bowyer_watson.triangles.iter().zip(bad_mask) {
+        if is_bad {
+            bad.push(*triangle);
+        } else {
+            good.push(*triangle);
+        }
+    }
+
+    // граница полости — рёбра, принадлежащие ровно одному удаляемому треугольнику
+    let mut edge_count: HashMap<(usize, usize), usize> = HashMap::new();
+    for [a, b, c] in &bad {
+        for (start, end) in [(*a, *b), (*b, *c), (*c, *a)] {
+            *edge_count.entry((start.min(end), start.max(end))).or_default() += 1;
+        }
+    }
+    let mut cavity: Vec<(usize, usize)> = edge_count
+        .into_iter()
+        .filter(|(_, count)| *count == 1)
+        .map(|(edge, _)| edge)
+        .collect();
+    cavity.sort();
+
+    let mut triangles = good;
+    // совпадающая с существующей точка не образует полости и пропускается
+    triangles.extend(cavity.iter().map(|&(start, end)| [start, end, index]));
+
+    state.bowyer_watson.triangles = triangles;
+    state.bowyer_watson.cavity = cavity;
+    state.bowyer_watson.inserted += 1;
+    state.current_point = Some(index);
+
+    // видимая часть — треугольники без вершин супертреугольника
+    state.triangles = state
+        .bowyer_watson
+        .triangles
+        .iter()
+        .filter(|triangle| triangle.iter().all(|&i| i < n))
+        .map(|&triangle| Polygon::from_poses(triangle))
+        .collect();
+
+    if state.bowyer_watson.inserted == n {
+        state.bowyer_watson.cavity.clear();
+        state.current_point = None;
+    }
+}
+
+/// Отметить треугольники полости точки `pos`: треугольники, в описанную окружность которых
+/// она попадает. Полость растёт от треугольника, содержащего точку, через общие рёбра,
+/// поэтому остаётся связной даже при погрешностях вычислений.
+fn cavity_mask(bw: &BowyerWatsonState, points: &[Pos2], pos: Pos2) -> Vec<bool> {
+    let vertexes = |triangle: &[usize; 3]| triangle.map(|i| bw.vertex(points, i));
+    let mut is_bad = vec![false; bw.triangles.len()];
+
+    let Some(start) = bw
+        .triangles
+        .iter()
+        .position(|triangle| triangle_contains(vertexes(triangle), pos))
+    else {
+        return is_bad;
+    };
+    if vertexes(&bw.triangles[start]).contains(&pos) {
+        return is_bad;
+    }
+
+    let mut neighbours: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, &[a, b, c]) in bw.triangles.iter().enumerate() {
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            neighbours.entry((start.min(end), start.max(end))).or_default().push(i);
+        }
+    }
+
+    is_bad[start] = true;
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        let [a, b, c] = bw.triangles[current];
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            for &other in &neighbours[&(start.min(end), start.max(end))] {
+                if !is_bad[other] && in_circumcircle(vertexes(&bw.triangles[other]), pos) {
+                    is_bad[other] = true;
+                    stack.push(other);
+                }
+            }
+        }
+    }
+    is_bad
+}
+
+/// Лежит ли точка внутри треугольника или на его границе.
+fn triangle_contains([a, b, c]: [Pos2; 3], pos: Pos2) -> bool {
+    let orientations = [orient2d(a, b, pos), orient2d(b, c, pos), orient2d(c, a, pos)];
+    orientations.iter().all(|o| *o >= 0.0) || orientations.iter().all(|o| *o <= 0.0)
+}
+
+/// Лежит ли точка строго внутри описанной окружности треугольника.
+fn in_circumcircle([a, b, c]: [Pos2; 3], pos: Pos2) -> bool {
+    let (orientation, inside) = (orient2d(a, b, c), incircle(a, b, c, pos));
+    (orientation > 0.0 && inside > 0.0) || (orientation < 0.0 && inside < 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::logic::{
+        generators::{GeneratorSettings, PointGenerator, generate_points},
+        triangulation::{Algorithm, convex_hull, power_triangulation},
+    };
+
+    /// Триангуляция точек генератора в квадрате 1000 x 1000 алгоритмом Бойера — Уотсона.
+    fn triangulate(generator: PointGenerator, count: usize) -> TriangulationState {
+        let settings = GeneratorSettings {
+            generator,
+            count,
+            seed: 5,
+            ..GeneratorSettings::default()
+        };
+        let bounds = egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(1000.0));
+        let mut state = TriangulationState {
+            points: generate_points(&settings, bounds),
+            algorithm: Algorithm::BowyerWatson,
+            ..TriangulationState::default()
+        };
+        power_triangulation(&mut state);
+        state
+    }
+
+    #[test]
+    fn triangulation_covers_convex_hull() {
+        for generator in [PointGenerator::Grid, PointGenerator::Circle, PointGenerator::Uniform] {
+            let state = triangulate(generator, 100);
+            let points = &state.points;
+            assert!(state.is_triangulation_completed(), "{generator:?}");
+
+            let hull = convex_hull(points);
+            let hull_edges: Vec<(Pos2, Pos2)> = (0..hull.len())
+                .map(|i| (points[hull[i]], points[hull[(i + 1) % hull.len()]]))
+                .collect();
+            // точки на границе оболочки, включая лежащие на её рёбрах
+            let boundary_count = points
+                .iter()
+                .filter(|p| {
+                    hull_edges.iter().any(|(a, b)| {
+                        orient2d(*a, *b, **p) == 0.0 && (**p - *a).dot(**p - *b) <= 0.0
+                    })
+                })
+                .count();
+            // по формуле Эйлера для триангуляции точек без совпадений
+            assert_eq!(state.triangles.len(), 2 * points.len() - 2 - boundary_count, "{generator:?}");
+
+            let hull_area = hull_edges.iter().map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f32>().abs() / 2.0;
+            let area: f32 = state.triangles.iter().map(|triangle| triangle.area(points)).sum();
+            assert!((area - hull_area).abs() <= hull_area * 1e-4, "{generator:?}: {area} != {hull_area}");
+        }
+    }
+}
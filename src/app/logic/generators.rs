@@ -0,0 +1,228 @@
+use egui::{Pos2, Rect};
+
+/// Доля меньшей стороны области, задающая радиус окружности генератора `Circle`.
+const CIRCLE_RADIUS_FACTOR: f32 = 0.4;
+/// Доля меньшей стороны области, задающая стандартное отклонение кластера.
+const CLUSTER_SIGMA_FACTOR: f32 = 0.08;
+/// Максимальное число попыток получить точку кластера внутри области.
+const CLUSTER_MAX_ATTEMPTS: usize = 100;
+
+// --------------------------------------------------
+// Настройки генерации
+// --------------------------------------------------
+
+/// Способ расстановки точек.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum PointGenerator {
+    /// Равномерно распределённые случайные точки.
+    #[default]
+    Uniform,
+    /// Узлы прямоугольной сетки.
+    Grid,
+    /// Равноотстоящие точки на окружности.
+    Circle,
+    /// Нормально распределённые кластеры.
+    Gaussian,
+}
+
+impl std::fmt::Display for PointGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointGenerator::Uniform => write!(f, "Равномерно"),
+            PointGenerator::Grid => write!(f, "Сетка"),
+            PointGenerator::Circle => write!(f, "Окружность"),
+            PointGenerator::Gaussian => write!(f, "Кластеры"),
+        }
+    }
+}
+
+/// Параметры генерации точек.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GeneratorSettings {
+    /// Способ расстановки.
+    pub generator: PointGenerator,
+    /// Количество точек.
+    pub count: usize,
+    /// Количество кластеров (для `Gaussian`).
+    pub clusters: usize,
+    /// Зерно генератора случайных чисел.
+    pub seed: u64,
+    /// Заменить существующие точки вместо добавления к ним.
+    pub replace: bool,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            generator: PointGenerator::default(),
+            count: 100,
+            clusters: 3,
+            seed: 1,
+            replace: true,
+        }
+    }
+}
+
+// --------------------------------------------------
+// Генерация
+// --------------------------------------------------
+
+/// Сгенерировать точки внутри `area` (в мировых координатах).
+pub fn generate_points(settings: &GeneratorSettings, area: Rect) -> Vec<Pos2> {
+    let count = settings.count;
+    let mut rng = Rng::new(settings.seed);
+    let min_side = area.width().min(area.height());
+
+    match settings.generator {
+        PointGenerator::Uniform => (0..count)
+            .map(|_| Pos2::new(rng.range(area.x_range()), rng.range(area.y_range())))
+            .collect(),
+        PointGenerator::Grid => {
+            if count == 0 {
+                return Vec::new();
+            }
+            // число столбцов подбирается под пропорции области
+            let aspect = area.width() / area.height().max(f32::EPSILON);
+            let columns = ((count as f32 * aspect).sqrt().ceil() as usize).clamp(1, count);
+            let rows = count.div_ceil(columns);
+            let step_x = area.width() / columns as f32;
+            let step_y = area.height() / rows as f32;
+            (0..count)
+                .map(|i| {
+                    let (row, column) = (i / columns, i % columns);
+                    area.min + egui::vec2((column as f32 + 0.5) * step_x, (row as f32 + 0.5) * step_y)
+                })
+                .collect()
+        }
+        PointGenerator::Circle => {
+            let radius = min_side * CIRCLE_RADIUS_FACTOR;
+            (0..count)
+                .map(|i| {
+                    let angle = std::f32::consts::TAU * i as f32 / count as f32;
+                    area.center() + radius * egui::vec2(angle.cos(), angle.sin())
+                })
+                .collect()
+        }
+        PointGenerator::Gaussian => {
+            let sigma = min_side * CLUSTER_SIGMA_FACTOR;
+            let centers: Vec<Pos2> = (0..settings.clusters.max(1))
+                .map(|_| Pos2::new(rng.range(area.x_range()), rng.range(area.y_range())))
+                .collect();
+            (0..count)
+                .map(|i| {
+                    let center = centers[i % centers.len()];
+                    // точки вне области отбрасываются: прижатые к границе совпадали бы
+                    (0..CLUSTER_MAX_ATTEMPTS)
+                        .map(|_| {
+                            let (dx, dy) = rng.normal_pair();
+                            center + sigma * egui::vec2(dx, dy)
+                        })
+                        .find(|point| area.contains(*point))
+                        .unwrap_or(center)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Простой генератор псевдослучайных чисел (SplitMix64): воспроизводим по зерну
+/// и не требует внешних зависимостей.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Равномерное число из [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Равномерное число из диапазона.
+    fn range(&mut self, range: egui::Rangef) -> f32 {
+        range.min + self.next_f32() * range.span()
+    }
+
+    /// Пара независимых стандартных нормальных величин (преобразование Бокса — Мюллера).
+    fn normal_pair(&mut self) -> (f32, f32) {
+        // 1 - u лежит в (0, 1], логарифм конечен
+        let radius = (-2.0 * (1.0 - self.next_f32()).ln()).sqrt();
+        let angle = std::f32::consts::TAU * self.next_f32();
+        (radius * angle.cos(), radius * angle.sin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATORS: [PointGenerator; 4] = [
+        PointGenerator::Uniform,
+        PointGenerator::Grid,
+        PointGenerator::Circle,
+        PointGenerator::Gaussian,
+    ];
+
+    fn settings(generator: PointGenerator, count: usize, seed: u64) -> GeneratorSettings {
+        GeneratorSettings {
+            generator,
+            count,
+            seed,
+            ..GeneratorSettings::default()
+        }
+    }
+
+    fn area() -> Rect {
+        Rect::from_min_size(Pos2::new(-50.0, 20.0), egui::vec2(300.0, 200.0))
+    }
+
+    #[test]
+    fn generates_requested_count_inside_area() {
+        for generator in GENERATORS {
+            for count in [0, 1, 7, 100] {
+                let points = generate_points(&settings(generator, count, 4), area());
+                assert_eq!(points.len(), count, "{generator:?}");
+                assert!(points.iter().all(|point| area().contains(*point)), "{generator:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_points() {
+        for generator in GENERATORS {
+            let first = generate_points(&settings(generator, 50, 9), area());
+            assert_eq!(generate_points(&settings(generator, 50, 9), area()), first, "{generator:?}");
+        }
+        let other = generate_points(&settings(PointGenerator::Uniform, 50, 10), area());
+        assert_ne!(other, generate_points(&settings(PointGenerator::Uniform, 50, 9), area()));
+    }
+
+    #[test]
+    fn gaussian_points_are_not_piled_on_border() {
+        // 500 точек одного кластера: часть выборки неизбежно попадает за границу области
+        let area = Rect::from_min_size(Pos2::ZERO, egui::vec2(100.0, 100.0));
+        let settings = GeneratorSettings {
+            clusters: 1,
+            ..settings(PointGenerator::Gaussian, 500, 3)
+        };
+        let points = generate_points(&settings, area);
+        let on_border = |point: &&Pos2| {
+            point.x == area.min.x || point.x == area.max.x || point.y == area.min.y || point.y == area.max.y
+        };
+        assert_eq!(points.iter().filter(on_border).count(), 0);
+
+        let mut unique = points.clone();
+        unique.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        unique.dedup();
+        assert_eq!(unique.len(), points.len());
+    }
+}
@@ -0,0 +1,212 @@
+use egui::Pos2;
+
+use crate::app::logic::predicates::orient2d;
+
+// --------------------------------------------------
+// Общие геометрические примитивы
+// --------------------------------------------------
+
+/// Точка пересечения прямых, проходящих через a, b и через c, d.
+///
+/// Прямые параллельны, если точки a и b одинаково удалены от прямой cd; это проверяется
+/// точным предикатом `orient2d`, без допусков. Для параллельных и совпадающих прямых,
+/// а также для вырожденных (a = b или c = d) возвращает `None`.
+pub fn line_intersection(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Option<Pos2> {
+    if a == b || c == d {
+        return None;
+    }
+    let (cda, cdb) = (orient2d(c, d, a), orient2d(c, d, b));
+    if cda == cdb {
+        return None;
+    }
+
+    // доля ab до точки пересечения — отношение расстояний от a и b до прямой cd
+    let t = cda / (cda - cdb);
+    let intersection = a + (b - a) * t as f32;
+    intersection.is_finite().then_some(intersection)
+}
+
+/// Точка пересечения отрезков ab и cd.
+///
+/// Взаимное расположение отрезков определяется точным предикатом `orient2d`, поэтому
+/// почти параллельные и касающиеся отрезки обрабатываются без допусков.
+/// Если отрезки лежат на одной прямой и перекрываются, возвращается ближайшая к `a`
+/// точка общей части.
+pub fn segment_intersection(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Option<Pos2> {
+    let (abc, abd) = (orient2d(a, b, c), orient2d(a, b, d));
+    let (cda, cdb) = (orient2d(c, d, a), orient2d(c, d, b));
+
+    if abc == 0.0 && abd == 0.0 && cda == 0.0 && cdb == 0.0 {
+        return collinear_overlap(a, b, c, d);
+    }
+
+    // концы одного отрезка строго по одну сторону от другого: пересечения нет
+    let same_side = |x: f64, y: f64| (x > 0.0 && y > 0.0) || (x < 0.0 && y < 0.0);
+    if same_side(abc, abd) || same_side(cda, cdb) {
+        return None;
+    }
+
+    // доля ab до точки пересечения — отношение расстояний от a и b до прямой cd
+    let t = cda / (cda - cdb);
+    Some(a + (b - a) * t as f32)
+}
+
+/// Ближайшая к `a` точка общей части отрезков ab и cd, лежащих на одной прямой.
+fn collinear_overlap(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Option<Pos2> {
+    let r = b - a;
+    let length_sq = r.length_sq();
+    if length_sq == 0.0 {
+        // отрезок ab вырожден в точку: она должна лежать на cd
+        let s = d - c;
+        let on_cd = if s.length_sq() == 0.0 {
+            a == c
+        } else {
+            (0.0..=1.0).contains(&((a - c).dot(s) / s.length_sq()))
+        };
+        return on_cd.then_some(a);
+    }
+
+    // проекции c и d на ab в параметрах отрезка ab
+    let t0 = (c - a).dot(r) / length_sq;
+    let t1 = (d - a).dot(r) / length_sq;
+    let start = t0.min(t1).max(0.0);
+    let end = t0.max(t1).min(1.0);
+    (start <= end).then(|| a + r * start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_intersect_beyond_segments() {
+        let intersection = line_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(3.0, 1.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(2.0, 2.0)));
+
+        let intersection = line_intersection(
+            Pos2::new(-5.0, 3.0),
+            Pos2::new(5.0, 3.0),
+            Pos2::new(1.0, -2.0),
+            Pos2::new(1.0, 7.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(1.0, 3.0)));
+    }
+
+    #[test]
+    fn parallel_and_coincident_lines_do_not_intersect() {
+        let parallel = line_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 2.0),
+            Pos2::new(1.0, 3.0),
+            Pos2::new(9.0, 7.0),
+        );
+        assert_eq!(parallel, None);
+
+        let coincident = line_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(5.0, 5.0),
+            Pos2::new(7.0, 7.0),
+        );
+        assert_eq!(coincident, None);
+
+        let degenerate = line_intersection(
+            Pos2::new(1.0, 1.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(0.0, 5.0),
+            Pos2::new(7.0, 5.0),
+        );
+        assert_eq!(degenerate, None);
+    }
+
+    #[test]
+    fn nearly_parallel_lines_still_intersect() {
+        // наклоны отличаются на одну единицу последнего разряда: допуск сделал бы их параллельными
+        let intersection = line_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(0.0, 1.0),
+            Pos2::new(1.0, 2.0f32.next_up()),
+        );
+        assert!(intersection.is_some());
+    }
+
+    #[test]
+    fn proper_crossing() {
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 4.0),
+            Pos2::new(0.0, 4.0),
+            Pos2::new(4.0, 0.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(2.0, 2.0)));
+    }
+
+    #[test]
+    fn touching_segments() {
+        // конец cd лежит внутри ab
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(1.0, 0.0),
+            Pos2::new(1.0, 3.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(1.0, 0.0)));
+
+        // общий конец
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(6.0, 3.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(4.0, 0.0)));
+    }
+
+    #[test]
+    fn parallel_segments_do_not_intersect() {
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(0.0, 1.0),
+            Pos2::new(4.0, 1.0),
+        );
+        assert_eq!(intersection, None);
+    }
+
+    #[test]
+    fn collinear_overlap_starts_nearest_to_a() {
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 0.0),
+            Pos2::new(6.0, 0.0),
+            Pos2::new(2.0, 0.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(2.0, 0.0)));
+
+        // ab целиком внутри cd
+        let intersection = segment_intersection(
+            Pos2::new(3.0, 3.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(0.0, 0.0),
+            Pos2::new(4.0, 4.0),
+        );
+        assert_eq!(intersection, Some(Pos2::new(3.0, 3.0)));
+    }
+
+    #[test]
+    fn disjoint_collinear_segments_do_not_intersect() {
+        let intersection = segment_intersection(
+            Pos2::new(0.0, 0.0),
+            Pos2::new(1.0, 1.0),
+            Pos2::new(2.0, 2.0),
+            Pos2::new(3.0, 3.0),
+        );
+        assert_eq!(intersection, None);
+    }
+}
@@ -1,9 +1,13 @@
+use egui::Pos2;
+
+use crate::app::logic::predicates::orient2d;
+
 // --------------------------------------------------
 // Реализация полигона
 // --------------------------------------------------
 
 /// Представление полигона. Точка и вектор тоже считаются полигонами.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct Polygon {
     pub a: usize,
     pub b: usize,
@@ -25,7 +29,126 @@ impl Polygon {
     }
 }
 
+// --------------------------------------------------
+// Геометрические характеристики
+// --------------------------------------------------
+impl Polygon {
+    /// Вершины полигона в порядке обхода a -> b -> c.
+    pub fn vertexes(&self, points: &[Pos2]) -> [Pos2; 3] {
+        [points[self.a], points[self.b], points[self.c]]
+    }
+
+    /// Рёбра полигона (начало, конец), включая замыкающее c -> a.
+    pub fn edges(&self, points: &[Pos2]) -> [(Pos2, Pos2); 3] {
+        let [a, b, c] = self.vertexes(points);
+        [(a, b), (b, c), (c, a)]
+    }
+
+    /// Ориентированная площадь полигона (половина `orient2d` для обхода a -> b -> c).
+    /// Знак определяет направление обхода: в экранных координатах (ось y вниз)
+    /// положительная площадь соответствует обходу по часовой стрелке. Знак точен и для почти
+    /// вырожденных полигонов: ноль получается только у точек на одной прямой.
+    pub fn signed_area(&self, points: &[Pos2]) -> f32 {
+        let [a, b, c] = self.vertexes(points);
+        (orient2d(a, b, c) / 2.0) as f32
+    }
+
+    /// Обходятся ли вершины a -> b -> c по часовой стрелке (на экране, ось y вниз).
+    pub fn is_clockwise(&self, points: &[Pos2]) -> bool {
+        self.signed_area(points) > 0.0
+    }
+
+    /// Индексы вершин в порядке обхода против часовой стрелки.
+    ///
+    /// Сам полигон хранит индексы упорядоченными (на этом основано сравнение и хеширование),
+    /// поэтому вместо разворота на месте возвращается новый порядок; форма при этом не меняется.
+    pub fn counter_clockwise_indices(&self, points: &[Pos2]) -> [usize; 3] {
+        if self.is_clockwise(points) {
+            [self.a, self.c, self.b]
+        } else {
+            [self.a, self.b, self.c]
+        }
+    }
+
+    /// Площадь полигона.
+    pub fn area(&self, points: &[Pos2]) -> f32 {
+        self.signed_area(points).abs()
+    }
+
+    /// Длины рёбер полигона в порядке `edges`.
+    pub fn edge_lengths(&self, points: &[Pos2]) -> Vec<f32> {
+        self.edges(points)
+            .iter()
+            .map(|(start, end)| (*end - *start).length())
+            .collect()
+    }
+
+    /// Индекс и длина самого длинного ребра (первого из равных).
+    pub fn longest_edge(&self, points: &[Pos2]) -> (usize, f32) {
+        let lengths = self.edge_lengths(points);
+        (1..lengths.len()).fold((0, lengths[0]), |best, i| {
+            if lengths[i] > best.1 { (i, lengths[i]) } else { best }
+        })
+    }
+
+    /// Индекс и длина самого короткого ребра (первого из равных).
+    pub fn shortest_edge(&self, points: &[Pos2]) -> (usize, f32) {
+        let lengths = self.edge_lengths(points);
+        (1..lengths.len()).fold((0, lengths[0]), |best, i| {
+            if lengths[i] < best.1 { (i, lengths[i]) } else { best }
+        })
+    }
+
+    /// Периметр полигона.
+    pub fn perimeter(&self, points: &[Pos2]) -> f32 {
+        self.edge_lengths(points).iter().sum()
+    }
+
+    /// Внутренние углы полигона (в радианах) при вершинах a, b, c.
+    pub fn angles(&self, points: &[Pos2]) -> [f32; 3] {
+        let [a, b, c] = self.vertexes(points);
+        [(a, c, b), (b, a, c), (c, b, a)].map(|(vertex, prev, next)| {
+            let (to_prev, to_next) = (prev - vertex, next - vertex);
+            let cross = to_prev.x * to_next.y - to_prev.y * to_next.x;
+            cross.abs().atan2(to_prev.dot(to_next))
+        })
+    }
+
+    /// Минимальный внутренний угол полигона (в радианах).
+    pub fn min_angle(&self, points: &[Pos2]) -> f32 {
+        self.angles(points).into_iter().fold(f32::INFINITY, f32::min)
+    }
+
+    /// Лежит ли точка внутри полигона (или на его границе).
+    pub fn contains(&self, points: &[Pos2], pos: Pos2) -> bool {
+        let sides = self.edges(points).map(|(start, end)| orient2d(start, end, pos));
+        sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+    }
+
+    /// Точка границы на расстоянии `s` вдоль периметра, начиная с вершины `a`.
+    /// Значение `s` берётся по модулю периметра. Для вырожденного полигона возвращает `None`.
+    pub fn point_at_arc_length(&self, points: &[Pos2], s: f32) -> Option<Pos2> {
+        let perimeter = self.perimeter(points);
+        if perimeter <= f32::EPSILON || !s.is_finite() {
+            return None;
+        }
+
+        let mut s = s.rem_euclid(perimeter);
+        for (start, end) in self.edges(points) {
+            let length = (end - start).length();
+            if s <= length && length > 0.0 {
+                return Some(start + (end - start) * (s / length));
+            }
+            s -= length;
+        }
+
+        // погрешность округления: точка совпадает с началом обхода
+        Some(points[self.a])
+    }
+}
+
 /// Настройка рисования полигона
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PolygonStyle {
     /// Цвет вершины полигона
     pub vertex_color: egui::Color32,
@@ -46,9 +169,39 @@ pub struct PolygonStyle {
     pub arrow_color: egui::Color32,
     /// Ширина стрелки
     pub arrow_width: f32,
+
+    /// Цвет заливки полигона
+    pub fill_color: egui::Color32,
+}
+
+/// Настраиваемые пользователем стили: стандартный и выбранный ("живой").
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StyleSettings {
+    /// Стандартный стиль (треугольники, точки, "мёртвые" рёбра).
+    pub standard: PolygonStyle,
+    /// Стиль выбранных элементов ("живые" рёбра, окружность шага).
+    pub selected: PolygonStyle,
+}
+
+impl Default for StyleSettings {
+    fn default() -> Self {
+        Self {
+            standard: PolygonStyle::dead(),
+            selected: PolygonStyle::alive(),
+        }
+    }
 }
 
 impl PolygonStyle {
+    /// Стиль из пользовательских настроек.
+    pub fn from_settings(settings: &StyleSettings, selected: bool) -> Self {
+        if selected {
+            settings.selected.clone()
+        } else {
+            settings.standard.clone()
+        }
+    }
+
     /// Стандартный стиль полигона
     pub fn dead() -> Self {
         PolygonStyle {
@@ -60,6 +213,7 @@ impl PolygonStyle {
             edge_width: 5.0,
             arrow_color: egui::Color32::LIGHT_BLUE,
             arrow_width: 1.0,
+            fill_color: egui::Color32::from_rgba_unmultiplied(128, 128, 128, 60),
         }
     }
 
@@ -74,6 +228,113 @@ impl PolygonStyle {
             edge_width: 7.0,
             arrow_color: egui::Color32::DARK_BLUE,
             arrow_width: 1.0,
+            fill_color: egui::Color32::from_rgba_unmultiplied(173, 216, 230, 80),
+        }
+    }
+
+    /// Стиль ребра и точки, обрабатываемых на текущем шаге
+    pub fn current() -> Self {
+        PolygonStyle {
+            vertex_color: egui::Color32::ORANGE,
+            vertex_radius: 10.0,
+            intersection_color: egui::Color32::RED,
+            intersection_radius: 7.0,
+            edge_color: egui::Color32::ORANGE,
+            edge_width: 7.0,
+            arrow_color: egui::Color32::RED,
+            arrow_width: 1.0,
+            fill_color: egui::Color32::from_rgba_unmultiplied(255, 165, 0, 80),
+        }
+    }
+
+    /// Стиль треугольников и точек, нарушающих свойство Делоне
+    pub fn violation() -> Self {
+        PolygonStyle {
+            vertex_color: egui::Color32::RED,
+            vertex_radius: 10.0,
+            intersection_color: egui::Color32::DARK_RED,
+            intersection_radius: 7.0,
+            edge_color: egui::Color32::RED,
+            edge_width: 3.0,
+            arrow_color: egui::Color32::DARK_RED,
+            arrow_width: 1.0,
+            fill_color: egui::Color32::from_rgba_unmultiplied(255, 0, 0, 60),
         }
     }
+
+    /// Стиль рёбер диаграммы Вороного
+    pub fn voronoi() -> Self {
+        PolygonStyle {
+            vertex_color: egui::Color32::DARK_GREEN,
+            vertex_radius: 4.0,
+            intersection_color: egui::Color32::DARK_GREEN,
+            intersection_radius: 3.0,
+            edge_color: egui::Color32::from_rgb(0, 160, 0),
+            edge_width: 2.0,
+            arrow_color: egui::Color32::DARK_GREEN,
+            arrow_width: 1.0,
+            fill_color: egui::Color32::from_rgba_unmultiplied(0, 160, 0, 40),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Прямоугольный треугольник 4-3-5 с периметром 12.
+    fn right_triangle() -> (Vec<Pos2>, Polygon) {
+        let points = vec![Pos2::new(0.0, 0.0), Pos2::new(4.0, 0.0), Pos2::new(4.0, 3.0)];
+        (points, Polygon::from_poses([0, 1, 2]))
+    }
+
+    #[test]
+    fn point_at_arc_length_wraps_around_perimeter() {
+        let (points, triangle) = right_triangle();
+        assert_eq!(triangle.perimeter(&points), 12.0);
+
+        // середина первого ребра и она же через полный обход
+        assert_eq!(triangle.point_at_arc_length(&points, 2.0), Some(Pos2::new(2.0, 0.0)));
+        assert_eq!(triangle.point_at_arc_length(&points, 14.0), Some(Pos2::new(2.0, 0.0)));
+        assert_eq!(
+            triangle.point_at_arc_length(&points, 0.5),
+            triangle.point_at_arc_length(&points, 12.5)
+        );
+        // отрицательная длина отсчитывается назад по последнему ребру
+        let back = triangle.point_at_arc_length(&points, -1.0).unwrap();
+        assert!((back - Pos2::new(0.8, 0.6)).length() < 1e-5);
+    }
+
+    #[test]
+    fn point_at_arc_length_of_degenerate_polygon_is_none() {
+        let points = vec![Pos2::new(1.0, 1.0); 3];
+        assert_eq!(Polygon::from_poses([0, 1, 2]).point_at_arc_length(&points, 0.5), None);
+    }
+
+    #[test]
+    fn longest_and_shortest_edges_of_scalene_triangle() {
+        let (points, triangle) = right_triangle();
+        assert_eq!(triangle.edge_lengths(&points), vec![4.0, 3.0, 5.0]);
+        assert_eq!(triangle.longest_edge(&points), (2, 5.0));
+        assert_eq!(triangle.shortest_edge(&points), (1, 3.0));
+    }
+
+    #[test]
+    fn signed_area_is_exact_far_from_origin() {
+        let (points, triangle) = right_triangle();
+        assert_eq!(triangle.signed_area(&points), 6.0);
+        assert!(triangle.is_clockwise(&points));
+
+        // формула шнурования в f32 теряет такую площадь в округлении произведений координат
+        let points = vec![
+            Pos2::new(1.0e6, 1.0e6),
+            Pos2::new(1.0e6 + 1.0, 1.0e6),
+            Pos2::new(1.0e6, 1.0e6 + 1.0),
+        ];
+        let triangle = Polygon::from_poses([0, 1, 2]);
+        assert_eq!(triangle.signed_area(&points), 0.5);
+        assert!(triangle.contains(&points, Pos2::new(1.0e6 + 0.25, 1.0e6 + 0.25)));
+        assert!(triangle.contains(&points, Pos2::new(1.0e6 + 0.5, 1.0e6 + 0.5)));
+        assert!(!triangle.contains(&points, Pos2::new(1.0e6 + 0.75, 1.0e6 + 0.75)));
+    }
 }
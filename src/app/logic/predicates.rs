@@ -0,0 +1,270 @@
+use egui::Pos2;
+
+/// Половина машинного эпсилона f64 (оценка относительной погрешности одной операции).
+const EPSILON: f64 = f64::EPSILON / 2.0;
+/// Относительная граница погрешности быстрого вычисления `orient2d`.
+const ORIENT_ERROR_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+/// Относительная граница погрешности быстрого вычисления `incircle`.
+const INCIRCLE_ERROR_BOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+// --------------------------------------------------
+// Адаптивные предикаты
+// --------------------------------------------------
+
+/// Удвоенная ориентированная площадь треугольника abc со строго правильным знаком.
+///
+/// Положительна, если c лежит справа от направленной прямой a -> b в экранных координатах
+/// (ось y вниз), отрицательна — если слева, и равна нулю только для точек на одной прямой.
+/// Сначала значение считается в f64 с оценкой погрешности; если её не хватает для определения
+/// знака, определитель вычисляется точно.
+pub fn orient2d(a: Pos2, b: Pos2, c: Pos2) -> f64 {
+    let [ax, ay, bx, by, cx, cy] = [a.x, a.y, b.x, b.y, c.x, c.y].map(f64::from);
+    let left = (ax - cx) * (by - cy);
+    let right = (ay - cy) * (bx - cx);
+    let determinant = left - right;
+    if determinant.abs() > ORIENT_ERROR_BOUND * (left.abs() + right.abs()) {
+        return determinant;
+    }
+
+    orient2d_exact(a, b, c)
+}
+
+/// Точное значение `orient2d` без быстрой проверки.
+fn orient2d_exact(a: Pos2, b: Pos2, c: Pos2) -> f64 {
+    let [ax, ay, bx, by, cx, cy] = [a.x, a.y, b.x, b.y, c.x, c.y].map(f64::from);
+    // произведения координат f32 в f64 точны, остаётся точно их сложить
+    [ax * by, -(ax * cy), -(ay * bx), ay * cx, bx * cy, -(by * cx)]
+        .into_iter()
+        .fold(Expansion::default(), |sum, term| sum.grow(term))
+        .estimate()
+}
+
+/// Определитель проверки попадания точки d в окружность, проходящую через a, b, c,
+/// со строго правильным знаком.
+///
+/// Если `orient2d(a, b, c)` положителен, результат положителен для d строго внутри окружности,
+/// отрицателен для d снаружи и равен нулю для d на окружности; при обратной ориентации
+/// треугольника знак меняется.
+pub fn incircle(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> f64 {
+    let [ax, ay, bx, by, cx, cy, dx, dy] = [a.x, a.y, b.x, b.y, c.x, c.y, d.x, d.y].map(f64::from);
+    let (adx, ady, bdx, bdy, cdx, cdy) = (ax - dx, ay - dy, bx - dx, by - dy, cx - dx, cy - dy);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let determinant = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    if determinant.abs() > INCIRCLE_ERROR_BOUND * permanent {
+        return determinant;
+    }
+
+    incircle_exact(a, b, c, d)
+}
+
+/// Точное значение `incircle` без быстрой проверки.
+fn incircle_exact(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> f64 {
+    let [ax, ay, bx, by, cx, cy, dx, dy] = [a.x, a.y, b.x, b.y, c.x, c.y, d.x, d.y].map(f64::from);
+    // разности координат представляются без потерь двумя компонентами
+    let [adx, ady, bdx, bdy, cdx, cdy] =
+        [(ax, dx), (ay, dy), (bx, dx), (by, dy), (cx, dx), (cy, dy)].map(|(p, q)| Expansion::difference(p, q));
+    let lift = |x: &Expansion, y: &Expansion| x.mul(x).sum(&y.mul(y));
+    let cross = |x1: &Expansion, y1: &Expansion, x2: &Expansion, y2: &Expansion| {
+        x1.mul(y2).sum(&y1.mul(x2).negate())
+    };
+
+    lift(&adx, &ady)
+        .mul(&cross(&bdx, &bdy, &cdx, &cdy))
+        .sum(&lift(&bdx, &bdy).mul(&cross(&cdx, &cdy, &adx, &ady)))
+        .sum(&lift(&cdx, &cdy).mul(&cross(&adx, &ady, &bdx, &bdy)))
+        .estimate()
+}
+
+// --------------------------------------------------
+// Точная арифметика разложений
+// --------------------------------------------------
+
+/// Число, представленное точной суммой неперекрывающихся f64-компонент
+/// в порядке возрастания модуля (арифметика разложений Шевчука).
+#[derive(Debug, Clone, Default)]
+struct Expansion(Vec<f64>);
+
+impl Expansion {
+    /// Точная разность `a - b`.
+    fn difference(a: f64, b: f64) -> Self {
+        let (sum, error) = two_sum(a, -b);
+        Self(Vec::new()).grow(error).grow(sum)
+    }
+
+    /// Прибавить число (GROW-EXPANSION с удалением нулевых компонент).
+    fn grow(self, value: f64) -> Self {
+        let mut components = Vec::with_capacity(self.0.len() + 1);
+        let mut carry = value;
+        for component in self.0 {
+            let (sum, error) = two_sum(carry, component);
+            if error != 0.0 {
+                components.push(error);
+            }
+            carry = sum;
+        }
+        if carry != 0.0 {
+            components.push(carry);
+        }
+        Self(components)
+    }
+
+    /// Сумма разложений. Добавление чисел по одной сохраняет неперекрываемость компонент.
+    fn sum(&self, other: &Self) -> Self {
+        other.0.iter().fold(self.clone(), |sum, component| sum.grow(*component))
+    }
+
+    /// Произведение на число: точные произведения компонент по одной добавляются к сумме,
+    /// поэтому результат остаётся неперекрывающимся.
+    fn scale(&self, value: f64) -> Self {
+        self.0.iter().fold(Self::default(), |product, component| {
+            let (high, low) = two_product(*component, value);
+            product.grow(low).grow(high)
+        })
+    }
+
+    /// Произведение разложений.
+    fn mul(&self, other: &Self) -> Self {
+        other
+            .0
+            .iter()
+            .fold(Self::default(), |product, component| product.sum(&self.scale(*component)))
+    }
+
+    /// Противоположное разложение.
+    fn negate(&self) -> Self {
+        Self(self.0.iter().map(|component| -component).collect())
+    }
+
+    /// Приближённое значение. Знак совпадает со знаком точного значения:
+    /// его определяет старшая компонента.
+    fn estimate(&self) -> f64 {
+        self.0.iter().sum()
+    }
+}
+
+/// Сумма и её точная ошибка округления: `a + b = sum + error`.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// Произведение и его точная ошибка округления: `a * b = product + error`.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Множитель, переводящий использованные в тестах f32 (кратные 2^-24) в целые числа.
+    const SCALE: f64 = (1 << 24) as f64;
+
+    fn to_int(p: Pos2) -> (i128, i128) {
+        let scaled = [p.x, p.y].map(|v| f64::from(v) * SCALE);
+        assert!(scaled.iter().all(|v| v.fract() == 0.0));
+        (scaled[0] as i128, scaled[1] as i128)
+    }
+
+    /// Знак результата предиката (-1, 0 или 1).
+    fn sign(value: f64) -> i128 {
+        if value > 0.0 {
+            1
+        } else if value < 0.0 {
+            -1
+        } else {
+            0
+        }
+    }
+
+    /// Точный знак `orient2d` в целых числах.
+    fn orient2d_reference(a: Pos2, b: Pos2, c: Pos2) -> i128 {
+        let [(ax, ay), (bx, by), (cx, cy)] = [a, b, c].map(to_int);
+        ((ax - cx) * (by - cy) - (ay - cy) * (bx - cx)).signum()
+    }
+
+    /// Точный знак `incircle` в целых числах.
+    fn incircle_reference(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> i128 {
+        let (dx, dy) = to_int(d);
+        let [(ax, ay), (bx, by), (cx, cy)] = [a, b, c].map(to_int).map(|(x, y)| (x - dx, y - dy));
+        let lift = |x: i128, y: i128| x * x + y * y;
+        (lift(ax, ay) * (bx * cy - cx * by) + lift(bx, by) * (cx * ay - ax * cy) + lift(cx, cy) * (ax * by - bx * ay))
+            .signum()
+    }
+
+    /// Сетка 32 x 32 точек вблизи (0.5, 0.5) с шагом в одну единицу последнего разряда f32.
+    fn near_half() -> impl Iterator<Item = Pos2> {
+        let ulp = 0.5f32.next_up() - 0.5;
+        (0..32 * 32).map(move |i| Pos2::new(0.5 + (i % 32) as f32 * ulp, 0.5 + (i / 32) as f32 * ulp))
+    }
+
+    #[test]
+    fn orient2d_sign_is_exact_near_collinear() {
+        let (b, c) = (Pos2::new(12.0, 12.0), Pos2::new(24.0, 24.0));
+        let mut signs = [0; 3];
+        for a in near_half() {
+            let expected = orient2d_reference(a, b, c);
+            assert_eq!(sign(orient2d(a, b, c)), expected, "{a:?}");
+            assert_eq!(sign(orient2d_exact(a, b, c)), expected, "{a:?}");
+            signs[(expected + 1) as usize] += 1;
+        }
+        // встречаются все три случая, включая точно лежащие на прямой
+        assert!(signs.iter().all(|count| *count > 0));
+    }
+
+    #[test]
+    fn incircle_sign_is_exact_near_cocircular() {
+        // окружность с центром (0.5, 0.5) через три точки с координатами, кратными 1/8
+        let (a, b, c) = (Pos2::new(0.5, 2.5), Pos2::new(2.5, 0.5), Pos2::new(0.5, -1.5));
+        let ulp = 0.5f32.next_up() - 0.5;
+        let mut signs = [0; 3];
+        for i in 0..32 * 32 {
+            let d = Pos2::new(-1.5 + (i % 32) as f32 * ulp * 4.0, 0.5 + (i / 32) as f32 * ulp);
+            let expected = incircle_reference(a, b, c, d);
+            assert_eq!(sign(incircle(a, b, c, d)), expected, "{d:?}");
+            assert_eq!(sign(incircle_exact(a, b, c, d)), expected, "{d:?}");
+            signs[(expected + 1) as usize] += 1;
+        }
+        assert!(signs.iter().all(|count| *count > 0));
+    }
+
+    #[test]
+    fn incircle_is_zero_for_large_pythagorean_points() {
+        // точки окружности x^2 + y^2 = 5^20 с целыми координатами, точно представимыми в f32
+        let points = [
+            Pos2::new(2734375.0, -9375000.0),
+            Pos2::new(9555375.0, 2015500.0),
+            Pos2::new(1476984.0, 9653287.0),
+            Pos2::new(5250000.0, 8234375.0),
+        ];
+        let [a, b, c, d] = points;
+
+        // обычное вычисление в f64 ошибается, хотя сами разности координат в нём точны
+        let [ax, ay, bx, by, cx, cy] = [a, b, c]
+            .map(|p| [f64::from(p.x) - f64::from(d.x), f64::from(p.y) - f64::from(d.y)])
+            .concat()
+            .try_into()
+            .unwrap();
+        let naive = (ax * ax + ay * ay) * (bx * cy - cx * by)
+            + (bx * bx + by * by) * (cx * ay - ax * cy)
+            + (cx * cx + cy * cy) * (ax * by - bx * ay);
+        assert_ne!(naive, 0.0);
+
+        assert_eq!(incircle(a, b, c, d), 0.0);
+        assert_eq!(incircle_exact(a, b, c, d), 0.0);
+        assert_ne!(orient2d(a, b, c), 0.0);
+    }
+}
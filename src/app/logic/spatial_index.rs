@@ -0,0 +1,179 @@
+use egui::{Pos2, Rect};
+
+/// Среднее число точек в одной ячейке сетки.
+const POINTS_PER_CELL: f32 = 2.0;
+
+// --------------------------------------------------
+// Равномерная сетка индексов точек
+// --------------------------------------------------
+
+/// Пространственный индекс: точки разложены по ячейкам равномерной сетки,
+/// покрывающей их ограничивающий прямоугольник.
+#[derive(Debug, Clone)]
+pub struct PointGrid {
+    /// Левый верхний угол сетки.
+    origin: Pos2,
+    /// Сторона ячейки.
+    cell_size: f32,
+    /// Количество столбцов.
+    columns: usize,
+    /// Количество строк.
+    rows: usize,
+    /// Индексы точек по ячейкам (построчно).
+    cells: Vec<Vec<usize>>,
+    /// Количество точек на момент построения.
+    point_count: usize,
+}
+
+impl PointGrid {
+    /// Построить индекс по набору точек.
+    pub fn new(points: &[Pos2]) -> Self {
+        let bounds = if points.is_empty() {
+            Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(1.0))
+        } else {
+            Rect::from_points(points)
+        };
+        let count = points.len().max(1) as f32;
+        let area = (bounds.width() * bounds.height()).max(f32::EPSILON);
+        // для точек вдоль одной прямой площадь вырождается: число ячеек вдоль длинной стороны
+        // всё равно не должно превышать число точек
+        let cell_size = (area * POINTS_PER_CELL / count)
+            .sqrt()
+            .max(bounds.width().max(bounds.height()) * POINTS_PER_CELL / count)
+            .max(f32::EPSILON);
+
+        let columns = (bounds.width() / cell_size).floor() as usize + 1;
+        let rows = (bounds.height() / cell_size).floor() as usize + 1;
+        let mut grid = Self {
+            origin: bounds.min,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            point_count: points.len(),
+        };
+        for (i, point) in points.iter().enumerate() {
+            let (column, row) = grid.cell_of(*point);
+            grid.cells[row * columns + column].push(i);
+        }
+        grid
+    }
+
+    /// Количество точек, по которым построен индекс.
+    pub fn point_count(&self) -> usize {
+        self.point_count
+    }
+
+    /// Ячейка (столбец, строка), содержащая точку. Точки вне сетки относятся к крайним ячейкам.
+    fn cell_of(&self, pos: Pos2) -> (usize, usize) {
+        let to_pos = (pos - self.origin) / self.cell_size;
+        (
+            (to_pos.x.max(0.0) as usize).min(self.columns - 1),
+            (to_pos.y.max(0.0) as usize).min(self.rows - 1),
+        )
+    }
+
+    /// Индексы точек из ячеек, пересекающих прямоугольник.
+    pub fn query_rect(&self, rect: Rect) -> impl Iterator<Item = usize> + '_ {
+        let (min_column, min_row) = self.cell_of(rect.min);
+        let (max_column, max_row) = self.cell_of(rect.max);
+        (min_row..=max_row)
+            .flat_map(move |row| (min_column..=max_column).map(move |column| row * self.columns + column))
+            .flat_map(|cell| self.cells[cell].iter().copied())
+    }
+
+    /// Индексы точек из ячеек "кольца" на расстоянии `ring` ячеек (по Чебышёву) от ячейки `center`.
+    /// Для кольца целиком за пределами сетки возвращает `None`.
+    pub fn query_ring(&self, center: Pos2, ring: usize) -> Option<Vec<usize>> {
+        let (column, row) = self.cell_of(center);
+        if ring > self.columns.max(self.rows) {
+            return None;
+        }
+
+        let (column, row, ring) = (column as isize, row as isize, ring as isize);
+        let mut ring_cells = Vec::new();
+        if ring == 0 {
+            ring_cells.push((column, row));
+        } else {
+            for c in (column - ring)..=(column + ring) {
+                ring_cells.push((c, row - ring));
+                ring_cells.push((c, row + ring));
+            }
+            for r in (row - ring + 1)..(row + ring) {
+                ring_cells.push((column - ring, r));
+                ring_cells.push((column + ring, r));
+            }
+        }
+
+        let mut found = Vec::new();
+        for (c, r) in ring_cells {
+            if r >= 0 && c >= 0 && (r as usize) < self.rows && (c as usize) < self.columns {
+                found.extend(&self.cells[r as usize * self.columns + c as usize]);
+            }
+        }
+        Some(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Решётка 10 x 10 с шагом 1.
+    fn lattice() -> Vec<Pos2> {
+        (0..100).map(|i| Pos2::new((i % 10) as f32, (i / 10) as f32)).collect()
+    }
+
+    #[test]
+    fn query_rect_returns_all_points_inside() {
+        let points = lattice();
+        let grid = PointGrid::new(&points);
+        assert_eq!(grid.point_count(), 100);
+
+        let rect = Rect::from_min_max(Pos2::new(2.5, 3.5), Pos2::new(6.0, 5.0));
+        let found: Vec<usize> = grid.query_rect(rect).collect();
+        // ячейки могут вернуть лишние точки, но не должны потерять нужные
+        for (i, point) in points.iter().enumerate() {
+            if rect.contains(*point) {
+                assert!(found.contains(&i), "{point:?}");
+            }
+        }
+        assert!(found.len() < points.len());
+
+        // прямоугольник, накрывающий всё, возвращает каждую точку ровно один раз
+        let mut all: Vec<usize> = grid.query_rect(Rect::EVERYTHING).collect();
+        all.sort();
+        assert_eq!(all, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn query_rings_partition_points() {
+        let points = lattice();
+        let grid = PointGrid::new(&points);
+        let center = Pos2::new(4.0, 4.0);
+
+        // кольца не пересекаются и вместе покрывают все точки
+        let mut all = Vec::new();
+        let mut ring = 0;
+        while let Some(found) = grid.query_ring(center, ring) {
+            all.extend(found);
+            ring += 1;
+        }
+        all.sort();
+        assert_eq!(all, (0..100).collect::<Vec<_>>());
+
+        // нулевое кольцо — ячейка самой точки
+        assert!(grid.query_ring(center, 0).unwrap().contains(&44));
+        // кольцо целиком за пределами сетки
+        assert_eq!(grid.query_ring(center, 1000), None);
+    }
+
+    #[test]
+    fn collinear_points_fit_into_grid() {
+        let points: Vec<Pos2> = (0..50).map(|i| Pos2::new(i as f32 * 3.0, 7.0)).collect();
+        let grid = PointGrid::new(&points);
+        let mut all: Vec<usize> = grid.query_rect(Rect::EVERYTHING).collect();
+        all.sort();
+        assert_eq!(all, (0..50).collect::<Vec<_>>());
+    }
+}
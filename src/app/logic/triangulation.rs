@@ -1,13 +1,45 @@
-use egui::Pos2;
-use std::{collections::{HashSet, VecDeque}, hash::Hash};
+use egui::{Pos2, emath::TSTransform};
+use std::{cmp::Ordering, collections::{HashMap, HashSet, VecDeque}, hash::Hash};
 
-use crate::app::logic::polygon::{Polygon, PolygonStyle};
+use crate::app::logic::{
+    bowyer_watson::{BowyerWatsonState, init_bowyer_watson, step_bowyer_watson},
+    geometry::segment_intersection,
+    polygon::{Polygon, PolygonStyle, StyleSettings},
+    predicates::{incircle, orient2d},
+    spatial_index::PointGrid,
+};
+
+/// Максимальное количество точек, добавляемых при улучшении сетки.
+const REFINE_MAX_POINTS: usize = 200;
+/// Минимальное расстояние от добавляемой при улучшении точки до существующих.
+const REFINE_MIN_DISTANCE: f32 = 1.0;
+
+/// Алгоритм построения триангуляции.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Algorithm {
+    /// Наращивание триангуляции по "живым" рёбрам.
+    #[default]
+    EdgeAdvancing,
+    /// Инкрементальная вставка точек (Бойер — Уотсон).
+    BowyerWatson,
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Algorithm::EdgeAdvancing => write!(f, "Живые рёбра"),
+            Algorithm::BowyerWatson => write!(f, "Бойер — Уотсон"),
+        }
+    }
+}
 
 /// Текущее состояние триангуляции Делоне.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct TriangulationState {
     /// Набор точек (вершин) для построение полигона.
     pub points: Vec<Pos2>,
+    /// Веса точек для взвешенной (степенной) триангуляции. Недостающие веса считаются нулевыми.
+    pub weights: Option<Vec<f32>>,
     /// Полученные полигоны.
     pub triangles: HashSet<Polygon>,
     /// "Живые" рёбра.
@@ -16,17 +48,38 @@ pub struct TriangulationState {
     pub edges_queue: VecDeque<Edge>,
     /// "Мёртвые" рёбра.
     pub dead_edges: HashSet<Edge>,
-    pub circle: Option<(Pos2, f32)>,
+    /// Описанная окружность (центр, радиус), использованная на последнем шаге.
+    /// Очищается после завершения триангуляции.
+    pub last_circumcircle: Option<(Pos2, f32)>,
+    /// Ребро, обработанное на последнем шаге.
+    pub current_edge: Option<Edge>,
+    /// Точка, к которой было достроено ребро на последнем шаге.
+    pub current_point: Option<usize>,
+    /// Используемый алгоритм.
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// Состояние алгоритма Бойера — Уотсона.
+    #[serde(default)]
+    pub bowyer_watson: BowyerWatsonState,
+    /// Пространственный индекс точек для поиска сопряжённых точек.
+    /// Строится при инициализации, при изменении набора точек не используется.
+    #[serde(skip)]
+    pub point_index: Option<PointGrid>,
 }
 
 impl TriangulationState {
-    fn draw_triangles(&self, painter: &egui::Painter, style: &PolygonStyle) {
+    /// Экранные координаты точки с учётом преобразования вида.
+    fn screen_point(&self, view: &TSTransform, index: usize) -> Pos2 {
+        *view * self.points[index]
+    }
+
+    fn draw_triangles(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
         self.triangles.iter().for_each(|triangle| {
             let points = vec![
-                self.points[triangle.a],
-                self.points[triangle.b],
-                self.points[triangle.c],
-                self.points[triangle.a],
+                self.screen_point(view, triangle.a),
+                self.screen_point(view, triangle.b),
+                self.screen_point(view, triangle.c),
+                self.screen_point(view, triangle.a),
             ];
             painter.line(
                 points,
@@ -35,60 +88,849 @@ impl TriangulationState {
         });
     }
 
-    fn draw_points(&self, painter: &egui::Painter, style: &PolygonStyle) {
-        self.points.iter().for_each(|point_pos| {
-            painter.circle_filled(*point_pos, style.vertex_radius, style.vertex_color);
+    /// Фигуры заливки: по одному выпуклому многоугольнику на каждый треугольник.
+    pub fn triangle_fill_shapes(&self, view: &TSTransform, style: &PolygonStyle) -> Vec<egui::Shape> {
+        self.triangles
+            .iter()
+            .map(|triangle| {
+                egui::Shape::convex_polygon(
+                    triangle.vertexes(&self.points).map(|point| *view * point).to_vec(),
+                    style.fill_color,
+                    egui::epaint::Stroke::NONE,
+                )
+            })
+            .collect()
+    }
+
+    fn fill_triangles(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
+        painter.extend(self.triangle_fill_shapes(view, style));
+    }
+
+    fn draw_points(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
+        self.points.iter().enumerate().for_each(|(i, point_pos)| {
+            painter.circle_filled(*view * *point_pos, style.vertex_radius, style.vertex_color);
+            // взвешенная точка — диск радиуса sqrt(вес)
+            let weight = point_weight(self.weights.as_deref(), i);
+            if weight > 0.0 {
+                painter.circle_stroke(
+                    *view * *point_pos,
+                    weight.sqrt() * view.scaling,
+                    egui::epaint::Stroke::new(style.arrow_width, style.edge_color),
+                );
+            }
         });
     }
 
-    fn draw_alive_edges(&self, painter: &egui::Painter, style: &PolygonStyle) {
+    fn draw_alive_edges(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
         self.alive_edges.iter().for_each(|edge| {
-            let edge_vec = self.points[edge.1] - self.points[edge.0];
+            let edge_vec = self.screen_point(view, edge.1) - self.screen_point(view, edge.0);
             painter.arrow(
-                self.points[edge.0],
+                self.screen_point(view, edge.0),
                 edge_vec,
                 egui::epaint::Stroke::new(style.edge_width, style.edge_color),
             );
         });
     }
 
-    fn draw_circle(&self, painter: &egui::Painter, style: &PolygonStyle) {
-        if let Some(circle) = self.circle {
+    fn draw_circle(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
+        if let Some(circle) = self.last_circumcircle {
             painter.circle_stroke(
-                circle.0,
-                circle.1,
-                egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+                *view * circle.0,
+                circle.1 * view.scaling,
+                egui::epaint::Stroke::new(style.arrow_width, style.edge_color.gamma_multiply(0.6)),
             );
         }
     }
 
-    fn draw_dead_edges(&self, painter: &egui::Painter, style: &PolygonStyle) {
+    fn draw_dead_edges(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
         self.dead_edges.iter().for_each(|edge| {
             painter.line_segment(
-                [self.points[edge.0], self.points[edge.1]],
+                [self.screen_point(view, edge.0), self.screen_point(view, edge.1)],
                 egui::epaint::Stroke::new(style.edge_width, style.edge_color),
             );
         });
     }
 
-    pub fn draw(&self, painter: &egui::Painter) {
-        self.draw_triangles(painter, &PolygonStyle::dead());
-        self.draw_points(painter, &PolygonStyle::dead());
-        self.draw_dead_edges(painter, &PolygonStyle::dead());
-        self.draw_alive_edges(painter, &PolygonStyle::alive());
-        self.draw_circle(painter, &PolygonStyle::alive());
+    fn draw_current_step(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
+        if let Some(edge) = self.current_edge {
+            painter.line_segment(
+                [self.screen_point(view, edge.0), self.screen_point(view, edge.1)],
+                egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+            );
+        }
+        if let Some(point) = self.current_point {
+            painter.circle_filled(self.screen_point(view, point), style.vertex_radius, style.vertex_color);
+        }
+    }
+
+    /// Нарисовать выпуклую оболочку точек.
+    pub fn draw_convex_hull(&self, painter: &egui::Painter, view: &TSTransform) {
+        let style = PolygonStyle::current();
+        let hull = convex_hull(&self.points);
+        if hull.len() < 2 {
+            return;
+        }
+        let points = hull.iter().map(|i| self.screen_point(view, *i)).collect();
+        painter.add(egui::Shape::closed_line(
+            points,
+            egui::epaint::Stroke::new(style.arrow_width, style.arrow_color),
+        ));
+    }
+
+    /// Граница полости, перестроенной на последнем шаге алгоритма Бойера — Уотсона.
+    /// Рёбра, идущие к вершинам супертреугольника, не рисуются.
+    fn draw_cavity(&self, painter: &egui::Painter, view: &TSTransform, style: &PolygonStyle) {
+        for (start, end) in self.visible_cavity() {
+            painter.line_segment(
+                [self.screen_point(view, start), self.screen_point(view, end)],
+                egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+            );
+        }
+    }
+
+    /// Рёбра границы полости между вставленными точками. Индексы от `point_count`
+    /// обозначают вершины супертреугольника, даже если после инициализации добавлены точки.
+    fn visible_cavity(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let n = self.bowyer_watson.point_count.min(self.points.len());
+        self.bowyer_watson
+            .cavity
+            .iter()
+            .copied()
+            .filter(move |&(start, end)| start < n && end < n)
+    }
+
+    /// Выделить треугольники и точки, нарушающие свойство пустой окружности,
+    /// и пересекающиеся рёбра.
+    pub fn draw_delaunay_violations(&self, painter: &egui::Painter, view: &TSTransform) {
+        let style = PolygonStyle::violation();
+        for (triangle, point) in self.delaunay_violations() {
+            let points = triangle.vertexes(&self.points).map(|p| *view * p).to_vec();
+            painter.add(egui::Shape::closed_line(
+                points,
+                egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+            ));
+            painter.circle_filled(self.screen_point(view, point), style.vertex_radius, style.vertex_color);
+        }
+
+        // пересекающиеся рёбра и точки их пересечения
+        for ((a, b), (c, d)) in self.crossing_edges() {
+            for (start, end) in [(a, b), (c, d)] {
+                painter.line_segment(
+                    [self.screen_point(view, start), self.screen_point(view, end)],
+                    egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+                );
+            }
+            let [a, b, c, d] = [a, b, c, d].map(|i| self.points[i]);
+            if let Some(intersection) = segment_intersection(a, b, c, d) {
+                painter.circle_filled(*view * intersection, style.intersection_radius, style.intersection_color);
+            }
+        }
+    }
+
+    /// Нарисовать границу альфа-формы завершённой триангуляции.
+    pub fn draw_alpha_shape(&self, painter: &egui::Painter, view: &TSTransform, alpha: f32) {
+        if !self.is_triangulation_completed() {
+            return;
+        }
+        let style = PolygonStyle::current();
+        for (start, end) in self.alpha_shape(alpha) {
+            painter.line_segment(
+                [self.screen_point(view, start), self.screen_point(view, end)],
+                egui::epaint::Stroke::new(style.edge_width / 2.0, style.edge_color),
+            );
+        }
+    }
+
+    /// Нарисовать диаграмму Вороного, двойственную к завершённой триангуляции:
+    /// рёбра ячеек и их вершины.
+    pub fn draw_voronoi(&self, painter: &egui::Painter, view: &TSTransform) {
+        let Some(diagram) = VoronoiDiagram::from_triangulation(self) else {
+            return;
+        };
+        let style = PolygonStyle::voronoi();
+        for cell in diagram.cells.iter().filter(|cell| cell.len() >= 3) {
+            painter.add(egui::Shape::convex_polygon(
+                cell.iter().map(|center| *view * *center).collect(),
+                style.fill_color,
+                egui::epaint::Stroke::NONE,
+            ));
+        }
+        for (start, end) in &diagram.edges {
+            painter.circle_filled(*view * *start, style.vertex_radius, style.vertex_color);
+            painter.circle_filled(*view * *end, style.vertex_radius, style.vertex_color);
+        }
+        for (start, end) in diagram.edges {
+            painter.line_segment(
+                [*view * start, *view * end],
+                egui::epaint::Stroke::new(style.edge_width, style.edge_color),
+            );
+        }
+    }
+
+    /// Нарисовать триангуляцию. Точки хранятся в мировых координатах и переводятся в экранные
+    /// преобразованием вида `view`. При `fill_triangles` треугольники заливаются полупрозрачным цветом.
+    pub fn draw(
+        &self,
+        painter: &egui::Painter,
+        view: &TSTransform,
+        styles: &StyleSettings,
+        fill_triangles: bool,
+    ) {
+        let standard = PolygonStyle::from_settings(styles, false);
+        let selected = PolygonStyle::from_settings(styles, true);
+        if fill_triangles {
+            self.fill_triangles(painter, view, &standard);
+        }
+        self.draw_triangles(painter, view, &standard);
+        self.draw_points(painter, view, &standard);
+        self.draw_dead_edges(painter, view, &standard);
+        self.draw_alive_edges(painter, view, &selected);
+        self.draw_circle(painter, view, &selected);
+        self.draw_cavity(painter, view, &selected);
+        self.draw_current_step(painter, view, &PolygonStyle::current());
     }
 
     pub fn is_triangulation_initialized(&self) -> bool {
-        return !self.alive_edges.is_empty() || !self.dead_edges.is_empty();
+        match self.algorithm {
+            Algorithm::EdgeAdvancing => !self.alive_edges.is_empty() || !self.dead_edges.is_empty(),
+            Algorithm::BowyerWatson => self.bowyer_watson.super_triangle.is_some(),
+        }
     }
 
     pub fn is_triangulation_completed(&self) -> bool {
-        return self.alive_edges.is_empty() && !self.dead_edges.is_empty();
+        match self.algorithm {
+            Algorithm::EdgeAdvancing => self.alive_edges.is_empty() && !self.dead_edges.is_empty(),
+            Algorithm::BowyerWatson => {
+                self.is_triangulation_initialized() && self.bowyer_watson.inserted == self.points.len()
+            }
+        }
+    }
+
+    /// Поддерживает ли алгоритм веса точек. Бойер — Уотсон строит только обычную триангуляцию.
+    pub fn supports_weights(algorithm: Algorithm) -> bool {
+        algorithm == Algorithm::EdgeAdvancing
+    }
+
+    /// Сменить алгоритм. Построенная триангуляция при этом сбрасывается.
+    /// Алгоритм без поддержки весов при заданных весах не выбирается.
+    pub fn set_algorithm(&mut self, algorithm: Algorithm) {
+        if self.weights.is_some() && !Self::supports_weights(algorithm) {
+            return;
+        }
+        if self.algorithm != algorithm {
+            self.algorithm = algorithm;
+            self.reset();
+        }
+    }
+
+    /// Объединить точки, находящиеся ближе `tolerance` друг к другу (с учётом транзитивности),
+    /// в их среднее. Возвращает количество удалённых точек.
+    ///
+    /// Индексы точек меняются, поэтому построенная триангуляция сбрасывается.
+    pub fn merge_close_points(&mut self, tolerance: f32) -> usize {
+        let n = self.points.len();
+
+        // система непересекающихся множеств
+        let mut parent: Vec<usize> = (0..n).collect();
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[i] = root;
+            root
+        }
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                if (self.points[i] - self.points[j]).length() <= tolerance {
+                    let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[root_i.max(root_j)] = root_i.min(root_j);
+                }
+            }
+        }
+
+        // кластеры в порядке первой точки
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        let mut cluster_of_root = HashMap::new();
+        for i in 0..n {
+            let root = find(&mut parent, i);
+            let cluster = *cluster_of_root.entry(root).or_insert_with(|| {
+                clusters.push(Vec::new());
+                clusters.len() - 1
+            });
+            clusters[cluster].push(i);
+        }
+
+        if clusters.len() == n {
+            return 0;
+        }
+
+        let weights = self.weights.as_deref();
+        let merged_weights = weights.map(|_| {
+            clusters
+                .iter()
+                .map(|cluster| {
+                    cluster.iter().map(|i| point_weight(weights, *i)).sum::<f32>() / cluster.len() as f32
+                })
+                .collect()
+        });
+        self.points = clusters
+            .iter()
+            .map(|cluster| {
+                let sum = cluster.iter().fold(egui::Vec2::ZERO, |sum, i| sum + self.points[*i].to_vec2());
+                (sum / cluster.len() as f32).to_pos2()
+            })
+            .collect();
+        self.weights = merged_weights;
+        self.reset();
+
+        n - self.points.len()
+    }
+
+    /// Задать вес точки. Недостающие веса дополняются нулями; если все веса нулевые,
+    /// триангуляция снова становится обычной. Построенная триангуляция сбрасывается.
+    /// Ненулевые веса переключают на алгоритм продвижения фронта, поддерживающий их.
+    pub fn set_weight(&mut self, index: usize, weight: f32) {
+        let weights = self.weights.get_or_insert_with(Vec::new);
+        weights.resize(weights.len().max(self.points.len()), 0.0);
+        weights[index] = weight;
+        if weights.iter().all(|weight| *weight == 0.0) {
+            self.weights = None;
+        } else if !Self::supports_weights(self.algorithm) {
+            self.algorithm = Algorithm::EdgeAdvancing;
+        }
+        self.reset();
+    }
+
+    /// Удалить все точки вместе с построенной триангуляцией.
+    pub fn clear_points(&mut self) {
+        *self = Self {
+            algorithm: self.algorithm,
+            ..Self::default()
+        };
+    }
+
+    /// Удалить последнюю добавленную точку. Построенная триангуляция при этом сбрасывается.
+    pub fn remove_last_point(&mut self) -> Option<Pos2> {
+        let point = self.points.pop()?;
+        if let Some(weights) = &mut self.weights {
+            weights.truncate(self.points.len());
+        }
+        self.reset();
+        Some(point)
+    }
+
+    /// Удалить точку по индексу. Индексы следующих точек сдвигаются,
+    /// поэтому построенная триангуляция сбрасывается.
+    pub fn remove_point(&mut self, index: usize) -> Pos2 {
+        let point = self.points.remove(index);
+        if let Some(weights) = &mut self.weights
+            && index < weights.len()
+        {
+            weights.remove(index);
+        }
+        self.reset();
+        point
+    }
+
+    /// Ближайшее к `pos` ребро триангуляции на расстоянии не больше `max_dist`:
+    /// индексы концов и ближайшая к `pos` точка ребра.
+    pub fn nearest_edge(&self, pos: Pos2, max_dist: f32) -> Option<((usize, usize), Pos2)> {
+        let mut edges: HashSet<(usize, usize)> = self
+            .alive_edges
+            .iter()
+            .chain(&self.dead_edges)
+            .map(|edge| (edge.0.min(edge.1), edge.0.max(edge.1)))
+            .collect();
+        for triangle in &self.triangles {
+            edges.extend([(triangle.a, triangle.b), (triangle.b, triangle.c), (triangle.a, triangle.c)]);
+        }
+
+        edges
+            .into_iter()
+            .map(|(start, end)| {
+                let (a, b) = (self.points[start], self.points[end]);
+                let ab = b - a;
+                let t = if ab.length_sq() > 0.0 {
+                    ((pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let projection = a + ab * t;
+                ((start, end), projection, (pos - projection).length())
+            })
+            .filter(|(_, _, distance)| *distance <= max_dist)
+            .min_by(|(e1, _, d1), (e2, _, d2)| d1.total_cmp(d2).then(e1.cmp(e2)))
+            .map(|(edge, projection, _)| (edge, projection))
+    }
+
+    /// Индекс ближайшей к `pos` точки на расстоянии не больше `max_dist`.
+    pub fn nearest_point(&self, pos: Pos2, max_dist: f32) -> Option<usize> {
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| (i, (*point - pos).length()))
+            .filter(|(_, distance)| *distance <= max_dist)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(i, _)| i)
+    }
+
+    /// Переместить точку. Построенная триангуляция при этом сбрасывается.
+    pub fn move_point(&mut self, index: usize, new_pos: Pos2) {
+        self.points[index] = new_pos;
+        self.reset();
+    }
+
+    /// Сбросить построенную триангуляцию, сохранив точки,
+    /// чтобы заново запустить `init_triangulation` на том же наборе.
+    pub fn reset(&mut self) {
+        self.triangles.clear();
+        self.alive_edges.clear();
+        self.edges_queue.clear();
+        self.dead_edges.clear();
+        self.last_circumcircle = None;
+        self.current_edge = None;
+        self.current_point = None;
+        self.bowyer_watson = BowyerWatsonState::default();
+        self.point_index = None;
+    }
+
+    /// Проверить загруженное состояние (например, файл проекта, изменённый вручную).
+    /// Точки с нечисловыми координатами удаляются, нечисловые веса обнуляются.
+    /// Если при этом что-то изменилось или построенная триангуляция ссылается на точки,
+    /// которых нет, она сбрасывается.
+    /// Возвращает, был ли выполнен сброс.
+    pub fn sanitize(&mut self) -> bool {
+        // точки с нечисловыми координатами отбрасываются вместе с весами; индексы при этом
+        // меняются, поэтому построенная триангуляция сбрасывается
+        let mut is_finite = true;
+        if self.points.iter().any(|point| !point.is_finite()) {
+            if let Some(weights) = &mut self.weights {
+                let points = &self.points;
+                *weights = weights
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| points.get(*i).is_some_and(|point| point.is_finite()))
+                    .map(|(_, weight)| *weight)
+                    .collect();
+            }
+            self.points.retain(|point| point.is_finite());
+            is_finite = false;
+        }
+        // нечисловые веса заменяются нулевыми
+        if let Some(weights) = &mut self.weights {
+            for weight in weights.iter_mut().filter(|weight| !weight.is_finite()) {
+                *weight = 0.0;
+                is_finite = false;
+            }
+            if weights.iter().all(|weight| *weight == 0.0) {
+                self.weights = None;
+            }
+        }
+
+        let n = self.points.len();
+        let in_range = |index: usize| index < n;
+        let edge_in_range = |edge: &Edge| in_range(edge.0) && in_range(edge.1);
+
+        // индексы от n до n + 2 — вершины супертреугольника Бойера — Уотсона
+        let bw = &self.bowyer_watson;
+        let bw_valid = match bw.super_triangle {
+            Some(_) => {
+                bw.point_count == n
+                    && bw.inserted <= n
+                    && bw.triangles.iter().flatten().all(|&index| index < n + 3)
+                    && bw.cavity.iter().all(|&(start, end)| start < n + 3 && end < n + 3)
+            }
+            None => bw.triangles.is_empty() && bw.cavity.is_empty(),
+        };
+
+        let is_valid = is_finite
+            && bw_valid
+            && self.triangles.iter().all(|t| in_range(t.a) && in_range(t.b) && in_range(t.c))
+            && self.alive_edges.iter().all(edge_in_range)
+            && self.dead_edges.iter().all(edge_in_range)
+            && self.edges_queue.iter().all(edge_in_range)
+            && self.current_edge.as_ref().is_none_or(edge_in_range)
+            && self.current_point.is_none_or(in_range);
+        if !is_valid {
+            self.reset();
+        }
+
+        // веса из файла проекта при алгоритме, который их не поддерживает
+        if self.weights.is_some() && !Self::supports_weights(self.algorithm) {
+            self.algorithm = Algorithm::EdgeAdvancing;
+            self.reset();
+        }
+
+        // пространственный индекс не сохраняется: без него продолжение триангуляции
+        // перебирало бы все точки на каждом шаге
+        if self.algorithm == Algorithm::EdgeAdvancing && self.is_triangulation_initialized() {
+            self.point_index = Some(PointGrid::new(&self.points));
+        }
+        !is_valid
+    }
+
+    /// Записать точки в формате CSV: по одной точке `x,y` на строку,
+    /// с необязательной строкой заголовка.
+    pub fn points_to_csv(&self, header: bool) -> String {
+        let mut csv = String::new();
+        if header {
+            csv.push_str("x,y\n");
+        }
+        for point in &self.points {
+            csv.push_str(&format!("{},{}\n", point.x, point.y));
+        }
+        csv
+    }
+
+    /// Записать сцену в формате SVG в мировых координатах и стилях `styles`:
+    /// треугольники (с заливкой при `fill_triangles`), "мёртвые" и "живые" рёбра и точки.
+    pub fn to_svg(&self, styles: &StyleSettings, fill_triangles: bool) -> String {
+        let standard = PolygonStyle::from_settings(styles, false);
+        let selected = PolygonStyle::from_settings(styles, true);
+
+        // поле вокруг точек, чтобы вершины не обрезались
+        let margin = standard.vertex_radius.max(selected.vertex_radius) + standard.edge_width;
+        let rect = if self.points.is_empty() {
+            egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(1.0))
+        } else {
+            egui::Rect::from_points(&self.points)
+        }
+        .expand(margin);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height()
+        );
+
+        let mut triangles: Vec<&Polygon> = self.triangles.iter().collect();
+        triangles.sort();
+        for triangle in triangles {
+            let points = triangle
+                .vertexes(&self.points)
+                .map(|point| format!("{},{}", point.x, point.y))
+                .join(" ");
+            let fill = if fill_triangles {
+                svg_paint("fill", standard.fill_color)
+            } else {
+                "fill=\"none\"".to_owned()
+            };
+            svg.push_str(&format!(
+                "  <polygon points=\"{points}\" {fill} {} stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n",
+                svg_paint("stroke", standard.edge_color),
+                standard.edge_width
+            ));
+        }
+
+        let sorted_edges = |edges: &HashSet<Edge>| {
+            let mut edges: Vec<Edge> = edges.iter().copied().collect();
+            edges.sort_by_key(|edge| (edge.0, edge.1));
+            edges
+        };
+        for (edges, style) in [(&self.dead_edges, &standard), (&self.alive_edges, &selected)] {
+            for edge in sorted_edges(edges) {
+                let (start, end) = (self.points[edge.0], self.points[edge.1]);
+                svg.push_str(&format!(
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-width=\"{}\" stroke-linecap=\"round\"/>\n",
+                    start.x,
+                    start.y,
+                    end.x,
+                    end.y,
+                    svg_paint("stroke", style.edge_color),
+                    style.edge_width
+                ));
+            }
+        }
+
+        for point in &self.points {
+            svg.push_str(&format!(
+                "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>\n",
+                point.x,
+                point.y,
+                standard.vertex_radius,
+                svg_paint("fill", standard.vertex_color)
+            ));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Улучшение сетки по упрощённому алгоритму Рапперта.
+    ///
+    /// Плохими считаются треугольники с минимальным углом меньше `min_angle_deg` (то есть
+    /// с отношением радиуса описанной окружности к кратчайшему ребру больше `1 / (2 sin θ)`)
+    /// или с площадью больше `max_area`. За один проход сначала делятся пополам рёбра границы,
+    /// в диаметральную окружность которых попадают точки; если таких нет, добавляются центры
+    /// описанных окружностей всех плохих треугольников. После прохода триангуляция строится
+    /// заново. Число добавленных точек ограничено `REFINE_MAX_POINTS`.
+    pub fn refine(&mut self, min_angle_deg: f32, max_area: f32) {
+        let max_ratio = 1.0 / (2.0 * min_angle_deg.to_radians().sin());
+        let mut added = 0;
+
+        power_triangulation(self);
+        while added < REFINE_MAX_POINTS {
+            let boundary: Vec<(Pos2, Pos2)> = self
+                .boundary_edges(self.triangles.iter())
+                .into_iter()
+                .map(|edge| (self.points[edge.0], self.points[edge.1]))
+                .collect();
+
+            let mut candidates: Vec<Pos2> = boundary
+                .iter()
+                .filter(|(start, end)| self.points.iter().any(|point| encroaches(*point, *start, *end)))
+                .map(|(start, end)| *start + (*end - *start) / 2.0)
+                .collect();
+            if candidates.is_empty() {
+                candidates = self
+                    .bad_triangles(max_ratio, max_area)
+                    .into_iter()
+                    .filter_map(|triangle| self.refinement_point(triangle, &boundary))
+                    .collect();
+            }
+
+            let mut new_points = Vec::new();
+            for point in candidates {
+                if added + new_points.len() == REFINE_MAX_POINTS {
+                    break;
+                }
+                // совпадающие точки ломают поиск сопряжённых точек
+                let min_distance = self
+                    .points
+                    .iter()
+                    .chain(&new_points)
+                    .map(|p| (*p - point).length())
+                    .fold(f32::INFINITY, f32::min);
+                if min_distance > REFINE_MIN_DISTANCE {
+                    new_points.push(point);
+                }
+            }
+            if new_points.is_empty() {
+                break;
+            }
+
+            added += new_points.len();
+            self.points.extend(new_points);
+            power_triangulation(self);
+        }
+    }
+
+    /// Треугольники, требующие улучшения: с отношением радиуса описанной окружности
+    /// к кратчайшему ребру больше `max_ratio` или с площадью больше `max_area`.
+    /// Начиная с самых вытянутых, затем самых крупных.
+    fn bad_triangles(&self, max_ratio: f32, max_area: f32) -> Vec<&Polygon> {
+        let mut bad_triangles: Vec<(&Polygon, f32, f32)> = self
+            .triangles
+            .iter()
+            .filter_map(|triangle| {
+                let [a, b, c] = triangle.vertexes(&self.points);
+                let (_, radius) = circumcircle(a, b, c)?;
+                let (_, shortest) = triangle.shortest_edge(&self.points);
+                Some((triangle, radius / shortest, triangle.area(&self.points)))
+            })
+            .filter(|(_, ratio, area)| *ratio > max_ratio || *area > max_area)
+            .collect();
+        bad_triangles.sort_by(|(t1, ratio1, area1), (t2, ratio2, area2)| {
+            ratio2.total_cmp(ratio1).then(area2.total_cmp(area1)).then(t1.cmp(t2))
+        });
+        bad_triangles.into_iter().map(|(triangle, _, _)| triangle).collect()
+    }
+
+    /// Точка, вставляемая для улучшения треугольника: центр его описанной окружности.
+    /// Если центр попадает в диаметральную окружность ребра границы `boundary`,
+    /// вместо него делится это ребро; если центр вне сетки — длиннейшее ребро треугольника.
+    fn refinement_point(&self, triangle: &Polygon, boundary: &[(Pos2, Pos2)]) -> Option<Pos2> {
+        let [a, b, c] = triangle.vertexes(&self.points);
+        let center = calculate_center(a, b, c)?;
+
+        if let Some((start, end)) = boundary.iter().find(|(start, end)| encroaches(center, *start, *end)) {
+            return Some(*start + (*end - *start) / 2.0);
+        }
+        if self.triangles.iter().any(|t| t.contains(&self.points, center)) {
+            return Some(center);
+        }
+        let (index, _) = triangle.longest_edge(&self.points);
+        let (start, end) = triangle.edges(&self.points)[index];
+        Some(start + (end - start) / 2.0)
+    }
+
+    /// Нарушения свойства пустой окружности: пары (треугольник, индекс точки), где точка
+    /// лежит строго внутри описанной окружности треугольника. Для корректной
+    /// триангуляции Делоне список пуст.
+    ///
+    /// Попадание в окружность проверяется точным предикатом `incircle`. Проверяются только
+    /// точки пространственного индекса из ограничивающего прямоугольника окружности.
+    ///
+    /// Для взвешенных точек вместо описанной окружности используется ортогональная:
+    /// точка нарушает свойство, если её степенное расстояние до окружности меньше её веса.
+    /// Такая точка может лежать где угодно, поэтому проверяются все точки.
+    pub fn delaunay_violations(&self) -> Vec<(Polygon, usize)> {
+        if let Some(weights) = self.weights.as_deref() {
+            return self.power_violations(weights);
+        }
+
+        // индекс, построенный при инициализации, годится, только если набор точек не менялся
+        let built_index;
+        let index = match &self.point_index {
+            Some(index) if index.point_count() == self.points.len() => index,
+            _ => {
+                built_index = PointGrid::new(&self.points);
+                &built_index
+            }
+        };
+
+        let mut violations = Vec::new();
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.vertexes(&self.points);
+            let orientation = orient2d(a, b, c);
+            if orientation == 0.0 {
+                continue;
+            }
+
+            let candidates: Vec<usize> = match circumcircle(a, b, c) {
+                Some((center, radius)) => {
+                    // запас на погрешность центра, чтобы не потерять точки у самой окружности
+                    let bounds = egui::Rect::from_center_size(center, egui::Vec2::splat(2.0 * radius))
+                        .expand(radius * 1e-3 + 1.0);
+                    index.query_rect(bounds).collect()
+                }
+                // центр почти вырожденного треугольника не представим в f32
+                None => (0..self.points.len()).collect(),
+            };
+            for i in candidates {
+                let is_vertex = i == triangle.a || i == triangle.b || i == triangle.c;
+                // знак incircle зависит от ориентации треугольника
+                if !is_vertex && incircle(a, b, c, self.points[i]) * orientation.signum() > 0.0 {
+                    violations.push((triangle.clone(), i));
+                }
+            }
+        }
+        violations.sort();
+        violations
+    }
+
+    /// Нарушения свойства пустой ортогональной окружности взвешенной триангуляции.
+    fn power_violations(&self, weights: &[f32]) -> Vec<(Polygon, usize)> {
+        let weight = |i: usize| point_weight(Some(weights), i);
+        let mut violations = Vec::new();
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.vertexes(&self.points);
+            let orientation = orient2d(a, b, c);
+            if orientation == 0.0 {
+                continue;
+            }
+            let [wa, wb, wc] = [triangle.a, triangle.b, triangle.c].map(weight);
+            for (i, point) in self.points.iter().enumerate() {
+                let is_vertex = i == triangle.a || i == triangle.b || i == triangle.c;
+                let inside = power_incircle([a, b, c, *point], [wa, wb, wc, weight(i)]);
+                if !is_vertex && inside * orientation.signum() > 0.0 {
+                    violations.push((triangle.clone(), i));
+                }
+            }
+        }
+        violations.sort();
+        violations
+    }
+
+    /// Пары пересекающихся рёбер треугольников (пары индексов концов). Рёбра с общей вершиной
+    /// не проверяются. Для корректной триангуляции список пуст.
+    ///
+    /// Рёбра упорядочиваются по левому концу, и каждое сравнивается только с рёбрами,
+    /// начинающимися не правее его правого конца.
+    pub fn crossing_edges(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut edges: Vec<(usize, usize)> = self
+            .triangles
+            .iter()
+            .flat_map(|triangle| [(triangle.a, triangle.b), (triangle.b, triangle.c), (triangle.a, triangle.c)])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let min_x = |(a, b): (usize, usize)| self.points[a].x.min(self.points[b].x);
+        let max_x = |(a, b): (usize, usize)| self.points[a].x.max(self.points[b].x);
+        edges.sort_by(|e1, e2| min_x(*e1).total_cmp(&min_x(*e2)).then(e1.cmp(e2)));
+
+        let mut crossings = Vec::new();
+        for (i, &(a, b)) in edges.iter().enumerate() {
+            for &(c, d) in edges[i + 1..].iter().take_while(|edge| min_x(**edge) <= max_x((a, b))) {
+                if a == c || a == d || b == c || b == d {
+                    continue;
+                }
+                let [pa, pb, pc, pd] = [a, b, c, d].map(|i| self.points[i]);
+                if segment_intersection(pa, pb, pc, pd).is_some() {
+                    crossings.push(((a, b), (c, d)));
+                }
+            }
+        }
+        crossings
+    }
+
+    /// Граничные рёбра альфа-формы (вогнутой оболочки) точек: пары индексов концов.
+    ///
+    /// Из завершённой триангуляции отбрасываются треугольники с радиусом описанной окружности
+    /// больше `alpha`, возвращаются рёбра, принадлежащие ровно одному оставшемуся треугольнику.
+    /// При достаточно большом `alpha` совпадает с выпуклой оболочкой.
+    pub fn alpha_shape(&self, alpha: f32) -> Vec<(usize, usize)> {
+        let triangles = self.triangles.iter().filter(|triangle| {
+            let [a, b, c] = triangle.vertexes(&self.points);
+            calculate_center(a, b, c).is_some_and(|center| (a - center).length() <= alpha)
+        });
+        self.boundary_edges(triangles)
+            .into_iter()
+            .map(|edge| (edge.0, edge.1))
+            .collect()
+    }
+
+    /// Упорядоченные циклы вершин границы завершённой триангуляции
+    /// (внешняя граница и границы дыр). Все циклы обходятся в одном направлении.
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+        for edge in self.boundary_edges(self.triangles.iter()) {
+            next.entry(edge.0).or_default().push(edge.1);
+        }
+
+        let mut starts: Vec<usize> = next.keys().copied().collect();
+        starts.sort();
+
+        let mut loops = Vec::new();
+        for start in starts {
+            while let Some(mut current) = next.get_mut(&start).and_then(|targets| targets.pop()) {
+                let mut boundary_loop = vec![start];
+                while current != start {
+                    boundary_loop.push(current);
+                    match next.get_mut(&current).and_then(|targets| targets.pop()) {
+                        Some(target) => current = target,
+                        // незамкнутая граница (например, незавершённая триангуляция)
+                        None => break,
+                    }
+                }
+                loops.push(boundary_loop);
+            }
+        }
+        loops
+    }
+
+    /// Граничные рёбра набора треугольников — рёбра, принадлежащие ровно одному из них.
+    /// Рёбра ориентированы одинаково относительно своих треугольников.
+    fn boundary_edges<'a>(&self, triangles: impl Iterator<Item = &'a Polygon>) -> Vec<Edge> {
+        let mut directed_edges = HashSet::new();
+        for triangle in triangles {
+            let [a, b, c] = triangle.counter_clockwise_indices(&self.points);
+            directed_edges.extend([Edge::new(a, b), Edge::new(b, c), Edge::new(c, a)]);
+        }
+
+        directed_edges
+            .iter()
+            .filter(|edge| !directed_edges.contains(&edge.reversed()))
+            .copied()
+            .collect()
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Edge(usize, usize);
 
 impl Edge {
@@ -103,6 +945,10 @@ impl Edge {
 
 /// Инициализировать триангуляцию вместе с выбором первого ребра.
 pub fn init_triangulation(state: &mut TriangulationState) {
+    if state.algorithm == Algorithm::BowyerWatson {
+        init_bowyer_watson(state);
+        return;
+    }
     if state.points.len() < 3 {
         return;
     }
@@ -112,6 +958,8 @@ pub fn init_triangulation(state: &mut TriangulationState) {
     state.edges_queue = VecDeque::new();
     state.dead_edges = HashSet::new();
 
+    state.point_index = Some(PointGrid::new(&state.points));
+
     let initial_edge = find_initial_edge(&state.points);
     state.alive_edges.insert(initial_edge);
     state.edges_queue.push_back(initial_edge);
@@ -119,7 +967,13 @@ pub fn init_triangulation(state: &mut TriangulationState) {
 
 /// Выполнить шаг триангуляции.
 pub fn step_triangulation(state: &mut TriangulationState) {
-    state.circle = None;
+    if state.algorithm == Algorithm::BowyerWatson {
+        step_bowyer_watson(state);
+        return;
+    }
+    state.last_circumcircle = None;
+    state.current_edge = None;
+    state.current_point = None;
     let mut current_edge;
     let mut right_point;
     // поиск живой вершины
@@ -139,7 +993,12 @@ pub fn step_triangulation(state: &mut TriangulationState) {
             continue;
         }
 
-        right_point = find_right_conjugate_point(&state.points, current_edge);
+        right_point = find_right_conjugate_point(
+            &state.points,
+            state.weights.as_deref(),
+            current_edge,
+            state.point_index.as_ref(),
+        );
         // нет правой сопряжённой точки => ребро принадлежит границе
         if right_point.is_none() {
             continue;
@@ -148,15 +1007,21 @@ pub fn step_triangulation(state: &mut TriangulationState) {
     }
 
     let best_point = right_point.unwrap();
+    state.current_edge = Some(current_edge);
+    state.current_point = Some(best_point);
 
-    let center = calculate_center(
-        state.points[current_edge.0],
-        state.points[current_edge.1],
-        state.points[best_point],
-    )
-    .unwrap();
-    let radius = (state.points[best_point] - center).length();
-    state.circle = Some((center, radius));
+    let weights = state.weights.as_deref();
+    // центр почти вырожденного треугольника может быть не представим в f32: окружность не рисуется
+    state.last_circumcircle =
+        calculate_weighted_center(&state.points, weights, [current_edge.0, current_edge.1, best_point]).map(
+            |center| {
+                // для взвешенных точек рисуется ортогональная окружность
+                let radius = ((state.points[best_point] - center).length_sq() - point_weight(weights, best_point))
+                    .max(0.0)
+                    .sqrt();
+                (center, radius)
+            },
+        );
 
     let new_triangle = Polygon::from_poses([current_edge.0, current_edge.1, best_point]);
     if state.triangles.contains(&new_triangle) {
@@ -197,35 +1062,196 @@ pub fn step_triangulation(state: &mut TriangulationState) {
         }
     }
     state.dead_edges.insert(current_edge);
+
+    if state.is_triangulation_completed() {
+        state.last_circumcircle = None;
+    }
 }
 
-/// Нахождение начального ребра для триангуляции Делоне.
-fn find_initial_edge(points: &[Pos2]) -> Edge {
-    // Нужна самая левая точка
-    let mut leftmost_idx = 0;
-    for i in 1..points.len() {
-        if points[i].x < points[leftmost_idx].x
-            || (points[i].x == points[leftmost_idx].x && points[i].y < points[leftmost_idx].y)
-        {
-            leftmost_idx = i;
+/// Выполнять шаги триангуляции до её завершения.
+///
+/// Ничего не делает, если триангуляция не инициализирована. Число шагов ограничено
+/// квадратом количества точек, чтобы вырожденный ввод не привёл к бесконечному циклу.
+pub fn complete_triangulation(state: &mut TriangulationState) {
+    if !state.is_triangulation_initialized() {
+        return;
+    }
+
+    let max_steps = state.points.len() * state.points.len();
+    for _ in 0..max_steps {
+        if state.is_triangulation_completed() {
+            break;
         }
+        step_triangulation(state);
     }
+}
 
-    // 2-ая точка ребра (по минимальному углу)
-    let mut best_idx = (leftmost_idx + 1) % points.len();
-    for i in 0..points.len() {
-        if i == leftmost_idx {
-            continue;
+/// Диаграмма Вороного, двойственная к триангуляции Делоне.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoronoiDiagram {
+    /// Рёбра: отрезки между центрами описанных окружностей треугольников с общим ребром.
+    pub edges: Vec<(Pos2, Pos2)>,
+    /// Ячейки точек: вершины ячейки в порядке обхода вокруг точки.
+    /// Ячейки точек выпуклой оболочки бесконечны и остаются пустыми.
+    pub cells: Vec<Vec<Pos2>>,
+}
+
+impl VoronoiDiagram {
+    /// Построить диаграмму по завершённой триангуляции. До завершения возвращает `None`.
+    ///
+    /// Бесконечные рёбра ячеек точек выпуклой оболочки опускаются.
+    pub fn from_triangulation(state: &TriangulationState) -> Option<Self> {
+        if !state.is_triangulation_completed() {
+            return None;
+        }
+
+        // индексы в треугольнике упорядочены, поэтому общее ребро совпадает у соседей
+        let mut edge_centers: HashMap<(usize, usize), Vec<Pos2>> = HashMap::new();
+        let mut point_centers: Vec<Vec<Pos2>> = vec![Vec::new(); state.points.len()];
+        for triangle in &state.triangles {
+            let [a, b, c] = triangle.vertexes(&state.points);
+            let Some(center) = calculate_center(a, b, c) else {
+                continue;
+            };
+            for edge in [(triangle.a, triangle.b), (triangle.b, triangle.c), (triangle.a, triangle.c)] {
+                edge_centers.entry(edge).or_default().push(center);
+            }
+            for point in [triangle.a, triangle.b, triangle.c] {
+                point_centers[point].push(center);
+            }
         }
-        let current_angle = angle_with_horizontal(&points[leftmost_idx], &points[i]);
-        let best_angle = angle_with_horizontal(&points[leftmost_idx], &points[best_idx]);
 
-        if current_angle < best_angle {
-            best_idx = i;
+        // точка внутренняя, если каждое её ребро разделяют два треугольника
+        let mut is_bounded = vec![true; state.points.len()];
+        for (&(start, end), centers) in &edge_centers {
+            if centers.len() != 2 {
+                is_bounded[start] = false;
+                is_bounded[end] = false;
+            }
         }
+
+        let cells = point_centers
+            .into_iter()
+            .enumerate()
+            .map(|(point, mut centers)| {
+                if !is_bounded[point] {
+                    return Vec::new();
+                }
+                let origin = state.points[point];
+                centers.sort_by(|u, v| {
+                    angle_with_horizontal(&origin, u).total_cmp(&angle_with_horizontal(&origin, v))
+                });
+                centers
+            })
+            .collect();
+
+        let edges = edge_centers
+            .into_values()
+            .filter_map(|centers| match centers[..] {
+                [first, second] => Some((first, second)),
+                _ => None,
+            })
+            .collect();
+
+        Some(Self { edges, cells })
     }
+}
 
-    Edge::new(leftmost_idx, best_idx)
+/// Рёбра диаграммы Вороного, двойственной к завершённой триангуляции.
+/// До завершения триангуляции возвращается пустой список.
+pub fn build_voronoi(state: &TriangulationState) -> Vec<(Pos2, Pos2)> {
+    VoronoiDiagram::from_triangulation(state)
+        .map(|diagram| diagram.edges)
+        .unwrap_or_default()
+}
+
+/// Выпуклая оболочка точек (алгоритм Эндрю). Возвращает индексы вершин оболочки
+/// в порядке обхода против часовой стрелки на экране (ось y вниз).
+///
+/// Для менее чем трёх точек и точек на одной прямой возвращается вырожденная оболочка:
+/// крайние точки.
+pub fn convex_hull(points: &[Pos2]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&i, &j| compare_points(points, i, j));
+    order.dedup_by(|i, j| points[*i] == points[*j]);
+    if order.len() < 3 {
+        return order;
+    }
+
+    // точка b не должна быть левее отрезка oa (иначе получится обход по часовой стрелке)
+    let turns_wrong = |o: usize, a: usize, b: usize| orient2d(points[o], points[a], points[b]) >= 0.0;
+
+    let mut hull: Vec<usize> = Vec::with_capacity(2 * order.len());
+    // нижняя цепочка
+    for &i in &order {
+        while hull.len() >= 2 && turns_wrong(hull[hull.len() - 2], hull[hull.len() - 1], i) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    // верхняя цепочка, начинающаяся с последней точки нижней
+    let lower_len = hull.len() + 1;
+    for &i in order.iter().rev().skip(1) {
+        while hull.len() >= lower_len && turns_wrong(hull[hull.len() - 2], hull[hull.len() - 1], i) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    hull.pop();
+    hull
+}
+
+/// Построить взвешенную (степенную) триангуляцию Делоне целиком.
+///
+/// Вместо описанной окружности используется степенное расстояние с весами `state.weights`.
+/// При равных весах результат совпадает с обычной триангуляцией Делоне.
+pub fn power_triangulation(state: &mut TriangulationState) {
+    init_triangulation(state);
+    complete_triangulation(state);
+}
+
+/// Нахождение начального ребра для триангуляции Делоне: ребра выпуклой оболочки,
+/// выходящего из самой левой точки, справа от которого лежат все остальные точки.
+fn find_initial_edge(points: &[Pos2]) -> Edge {
+    let hull = convex_hull(points);
+    // все точки совпадают
+    if hull.len() < 2 {
+        return Edge::new(hull[0], (hull[0] + 1) % points.len());
+    }
+
+    // оболочка обходится так, что остальные точки слева от её рёбер,
+    // поэтому берётся ребро к предыдущей вершине
+    let start = hull[0];
+    let hull_end = hull[hull.len() - 1];
+
+    // оболочка не содержит точек, лежащих на её рёбрах: берётся ближайшая из них
+    let origin = points[start];
+    let direction = points[hull_end] - origin;
+    let end = (0..points.len())
+        .filter(|&i| {
+            points[i] != origin
+                && orient2d(origin, points[hull_end], points[i]) == 0.0
+                && direction.dot(points[i] - origin) > 0.0
+        })
+        .min_by(|&i, &j| {
+            (points[i] - origin)
+                .length_sq()
+                .total_cmp(&(points[j] - origin).length_sq())
+                .then(compare_points(points, i, j))
+        })
+        .unwrap_or(hull_end);
+
+    Edge::new(start, end)
+}
+
+/// Порядок точек для детерминированного разрешения равенств:
+/// по координатам x, затем y, затем по индексу.
+fn compare_points(points: &[Pos2], i: usize, j: usize) -> Ordering {
+    points[i]
+        .x
+        .total_cmp(&points[j].x)
+        .then(points[i].y.total_cmp(&points[j].y))
+        .then(i.cmp(&j))
 }
 
 /// Угол ребра к горизонтали
@@ -235,93 +1261,869 @@ fn angle_with_horizontal(p1: &Pos2, p2: &Pos2) -> f32 {
     dy.atan2(dx)
 }
 
-/// Нахождение правой сопряжённой точки
-fn find_right_conjugate_point(points: &[Pos2], edge: Edge) -> Option<usize> {
+/// Нахождение правой сопряжённой точки.
+///
+/// Если передан актуальный пространственный индекс (и точки не взвешены), перебираются только
+/// точки вблизи ребра: сначала ближайший кандидат по кольцам ячеек вокруг середины ребра,
+/// затем точки в ограничивающем прямоугольнике его окружности — лучшая точка может лежать
+/// только внутри неё.
+fn find_right_conjugate_point(
+    points: &[Pos2],
+    weights: Option<&[f32]>,
+    edge: Edge,
+    index: Option<&PointGrid>,
+) -> Option<usize> {
+    let Some(index) = index.filter(|index| weights.is_none() && index.point_count() == points.len()) else {
+        return best_conjugate_point(points, weights, edge, 0..points.len());
+    };
+
+    let (p1, p2) = (points[edge.0], points[edge.1]);
+    let mid_edge = Pos2::new((p1.x + p2.x) / 2.0, (p1.y + p2.y) / 2.0);
+    let mut ring = 0;
+    let candidate = loop {
+        let candidates = index.query_ring(mid_edge, ring)?;
+        if let Some(candidate) = best_conjugate_point(points, weights, edge, candidates) {
+            break candidate;
+        }
+        ring += 1;
+    };
+
+    // центр почти вырожденного треугольника не представим в f32: перебираются все точки
+    let Some(center) = calculate_weighted_center(points, weights, [edge.0, edge.1, candidate]) else {
+        return best_conjugate_point(points, weights, edge, 0..points.len());
+    };
+    let radius = (p1 - center).length();
+    // запас на погрешность, чтобы не потерять точки на самой окружности
+    let bounds = egui::Rect::from_center_size(center, egui::Vec2::splat(2.0 * radius)).expand(radius * 1e-3 + 1.0);
+    best_conjugate_point(points, weights, edge, index.query_rect(bounds))
+}
+
+/// Лучшая правая сопряжённая точка ребра среди кандидатов.
+///
+/// Без весов точка лучше текущей, если попадает внутрь окружности, проходящей через ребро
+/// и текущую точку; это проверяется точным предикатом `incircle`. Со взвешенными точками
+/// сравниваются расстояния от середины ребра до степенных центров.
+fn best_conjugate_point(
+    points: &[Pos2],
+    weights: Option<&[f32]>,
+    edge: Edge,
+    candidates: impl IntoIterator<Item = usize>,
+) -> Option<usize> {
     let p1 = points[edge.0];
     let p2 = points[edge.1];
 
-    let mut best_point = None;
+    let mut best_point: Option<usize> = None;
     let mut best_distance = f32::INFINITY;
 
-    for i in 0..points.len() {
+    for i in candidates {
         if i == edge.0 || i == edge.1 {
             continue;
         }
 
         let p3 = points[i];
-        // точка должна быть справа от ребра
-        if is_point_left(p3, p1, p2) {
+        // точка должна быть справа от ребра (на самом ребре окружность не существует)
+        if !is_point_right(p3, p1, p2) {
             continue;
         }
 
-        // расстояние до центра описанной
-        if let Some(center) = calculate_center(p1, p2, p3) {
-            let mid_edge = Pos2::new((p1.x + p2.x) / 2.0, (p1.y + p2.y) / 2.0);
-            let vec_to_center = center - mid_edge;
-
-            let distance: f32;
-            if is_point_right(center, p1, p2) {
-                distance = vec_to_center.length();
-            }
-            else {
-                distance = -vec_to_center.length()
+        let ordering = match (weights, best_point) {
+            (None, None) => Ordering::Less,
+            (None, Some(best)) => {
+                // треугольник (p1, p2, best) ориентирован положительно
+                let inside = incircle(p1, p2, points[best], p3);
+                0.0.partial_cmp(&inside).unwrap_or(Ordering::Equal)
             }
+            (Some(_), _) => {
+                // расстояние до центра описанной
+                let Some(center) = calculate_weighted_center(points, weights, [edge.0, edge.1, i]) else {
+                    continue;
+                };
+                let mid_edge = Pos2::new((p1.x + p2.x) / 2.0, (p1.y + p2.y) / 2.0);
+                let vec_to_center = center - mid_edge;
 
-            if distance < best_distance {
-                best_distance = distance;
-                best_point = Some(i);
+                let distance: f32;
+                if is_point_right(center, p1, p2) {
+                    distance = vec_to_center.length();
+                }
+                else {
+                    distance = -vec_to_center.length()
+                }
+                let ordering = distance.total_cmp(&best_distance);
+                if ordering == Ordering::Less {
+                    best_distance = distance;
+                }
+                ordering
             }
+        };
+
+        // при равенстве (точки на одной окружности) выбор не зависит от порядка обхода
+        let is_better = ordering == Ordering::Less
+            || (ordering == Ordering::Equal
+                && best_point.is_some_and(|best| match weights {
+                    None => is_cocircular_point_better(points, edge, best, i),
+                    Some(_) => compare_points(points, i, best) == Ordering::Less,
+                }));
+        if is_better {
+            best_point = Some(i);
         }
     }
 
     best_point
 }
 
-/// Нахождение центра окружности, проходящей через точки a, b, c.
-fn calculate_center(a: Pos2, b: Pos2, c: Pos2) -> Option<Pos2> {
-    // срединный перпендикуляр к ab
-    let ab = b - a;
-    let n_ab_start = Pos2::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
-    let n_ab_end = Pos2::new(n_ab_start.x + ab.y, n_ab_start.y - ab.x);
+/// Выбор между точками `best` и `candidate`, лежащими на одной окружности с ребром.
+///
+/// Точки мысленно приподнимаются над параболоидом на бесконечно малые величины, убывающие
+/// в порядке `compare_points`, и решает самая "старшая" из четырёх точек. Так все рёбра
+/// разрешают равенства одинаково, и на решётках соседние фронты не строят пересекающиеся диагонали.
+fn is_cocircular_point_better(points: &[Pos2], edge: Edge, best: usize, candidate: usize) -> bool {
+    let dominant = [edge.0, edge.1, best, candidate]
+        .into_iter()
+        .max_by(|&i, &j| compare_points(points, i, j))
+        .expect("четыре точки");
+    // кандидат ближе по дуге к вершине `near`, чем `best`
+    let is_closer_to = |near: usize, far: usize| {
+        let (side_near, side_candidate) = (
+            orient2d(points[far], points[best], points[near]),
+            orient2d(points[far], points[best], points[candidate]),
+        );
+        (side_near > 0.0) == (side_candidate > 0.0)
+    };
 
-    // срединный перпендикуляр к ac
-    let ac = c - a;
-    let n_ac_start = Pos2::new((a.x + c.x) / 2.0, (a.y + c.y) / 2.0);
-    let n_ac_end = Pos2::new(n_ac_start.x + ac.y, n_ac_start.y - ac.x);
+    if dominant == candidate {
+        false
+    } else if dominant == best {
+        true
+    } else if dominant == edge.0 {
+        is_closer_to(edge.0, edge.1)
+    } else {
+        is_closer_to(edge.1, edge.0)
+    }
+}
 
-    lines_intersect(n_ab_start, n_ab_end, n_ac_start, n_ac_end)
+/// Атрибуты цвета SVG (`fill`/`stroke` и соответствующая прозрачность).
+fn svg_paint(attribute: &str, color: egui::Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format!(
+        "{attribute}=\"#{r:02x}{g:02x}{b:02x}\" {attribute}-opacity=\"{}\"",
+        a as f32 / 255.0
+    )
 }
 
-fn is_point_right(point: Pos2, start: Pos2, end: Pos2) -> bool {
-    let segment_vector = end - start;
-    let point_vector = point - start;
+/// Окружность (центр, радиус), проходящая через точки a, b, c.
+/// Для точек на одной прямой возвращает `None`.
+pub fn circumcircle(a: Pos2, b: Pos2, c: Pos2) -> Option<(Pos2, f32)> {
+    let center = calculate_center(a, b, c)?;
+    Some((center, (a - center).length()))
+}
 
-    // векторное произведение
-    let cross_product = segment_vector.x * point_vector.y - segment_vector.y * point_vector.x;
-    cross_product > 0.0
+/// Нахождение центра окружности, проходящей через точки a, b, c
+/// (степенной центр точек с нулевыми весами).
+fn calculate_center(a: Pos2, b: Pos2, c: Pos2) -> Option<Pos2> {
+    calculate_power_center([a, b, c], [0.0; 3])
 }
 
-fn is_point_left(point: Pos2, start: Pos2, end: Pos2) -> bool {
-    let segment_vector = end - start;
-    let point_vector = point - start;
+/// Лежит ли точка строго внутри диаметральной окружности отрезка start -> end
+/// (не считая самих концов).
+fn encroaches(point: Pos2, start: Pos2, end: Pos2) -> bool {
+    let middle = start + (end - start) / 2.0;
+    point != start && point != end && (point - middle).length_sq() < (end - start).length_sq() / 4.0
+}
 
-    // векторное произведение
-    let cross_product = segment_vector.x * point_vector.y - segment_vector.y * point_vector.x;
-    cross_product < 0.0
+/// Вес точки с указанным индексом.
+fn point_weight(weights: Option<&[f32]>, index: usize) -> f32 {
+    weights
+        .and_then(|weights| weights.get(index).copied())
+        .unwrap_or(0.0)
 }
 
-/// Проверка пересечения двух отрезков ab и cd
-fn lines_intersect(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Option<Pos2> {
-    let denominator = (a.x - b.x) * (c.y - d.y) - (a.y - b.y) * (c.x - d.x);
-    if denominator.abs() < f32::EPSILON {
+/// Нахождение центра окружности для точек с указанными индексами с учётом весов.
+fn calculate_weighted_center(
+    points: &[Pos2],
+    weights: Option<&[f32]>,
+    indexes: [usize; 3],
+) -> Option<Pos2> {
+    calculate_power_center(indexes.map(|i| points[i]), indexes.map(|i| point_weight(weights, i)))
+}
+
+/// Нахождение степенного центра взвешенных точек a, b, c
+/// (точки, степенные расстояния от которой до всех трёх точек равны).
+/// При равных весах совпадает с центром описанной окружности.
+///
+/// Вырожденность определяется точным предикатом ориентации, сам центр считается в f64.
+fn calculate_power_center(
+    [a, b, c]: [Pos2; 3],
+    [wa, wb, wc]: [f32; 3],
+) -> Option<Pos2> {
+    if orient2d(a, b, c) == 0.0 {
         return None;
     }
 
-    let numerator_x = (a.x * b.y - a.y * b.x) * (c.x - d.x) - (a.x - b.x) * (c.x * d.y - c.y * d.x);
-    let numerator_y = (a.x * b.y - a.y * b.x) * (c.y - d.y) - (a.y - b.y) * (c.x * d.y - c.y * d.x);
+    // координаты b и c относительно a
+    let (ax, ay) = (a.x as f64, a.y as f64);
+    let (bx, by) = (b.x as f64 - ax, b.y as f64 - ay);
+    let (cx, cy) = (c.x as f64 - ax, c.y as f64 - ay);
+    let denominator = 2.0 * (bx * cy - by * cx);
+
+    // |x - a|^2 - wa = |x - b|^2 - wb  =>  2 * ab * (x - a) = |ab|^2 - (wb - wa)
+    let (wa, wb, wc) = (wa as f64, wb as f64, wc as f64);
+    let rb = bx * bx + by * by - (wb - wa);
+    let rc = cx * cx + cy * cy - (wc - wa);
+
+    let center = Pos2::new(
+        (ax + (rb * cy - rc * by) / denominator) as f32,
+        (ay + (bx * rc - cx * rb) / denominator) as f32,
+    );
+    center.is_finite().then_some(center)
+}
+
+/// Аналог `incircle` для взвешенных точек: определитель с "поднятыми" координатами
+/// `|p - d|^2 - w_p + w_d`. При положительной ориентации abc положителен, если степенное
+/// расстояние от d до ортогональной окружности a, b, c меньше веса d.
+///
+/// Считается в f64 без точной арифметики: с весами точные предикаты не используются.
+fn power_incircle([a, b, c, d]: [Pos2; 4], [wa, wb, wc, wd]: [f32; 4]) -> f64 {
+    let lifted = |p: Pos2, w: f32| {
+        let (x, y) = (p.x as f64 - d.x as f64, p.y as f64 - d.y as f64);
+        (x, y, x * x + y * y - w as f64 + wd as f64)
+    };
+    let [(ax, ay, al), (bx, by, bl), (cx, cy, cl)] = [(a, wa), (b, wb), (c, wc)].map(|(p, w)| lifted(p, w));
+    al * (bx * cy - cx * by) + bl * (cx * ay - ax * cy) + cl * (ax * by - bx * ay)
+}
+
+/// Лежит ли точка строго справа от направленной прямой start -> end (точный предикат).
+fn is_point_right(point: Pos2, start: Pos2, end: Pos2) -> bool {
+    orient2d(start, end, point) > 0.0
+}
+
+/// Лежит ли точка строго слева от направленной прямой start -> end (точный предикат).
+fn is_point_left(point: Pos2, start: Pos2, end: Pos2) -> bool {
+    orient2d(start, end, point) < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::logic::generators::{GeneratorSettings, PointGenerator, generate_points};
+    use std::collections::BTreeSet;
+
+    /// Точки заданного генератора в квадрате 1000 x 1000.
+    fn sample_points(generator: PointGenerator, count: usize, seed: u64) -> Vec<Pos2> {
+        let settings = GeneratorSettings {
+            generator,
+            count,
+            seed,
+            ..GeneratorSettings::default()
+        };
+        generate_points(&settings, egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(1000.0)))
+    }
+
+    /// Построить триангуляцию точек целиком.
+    fn triangulate(points: Vec<Pos2>, algorithm: Algorithm) -> TriangulationState {
+        let mut state = TriangulationState {
+            points,
+            algorithm,
+            ..TriangulationState::default()
+        };
+        init_triangulation(&mut state);
+        complete_triangulation(&mut state);
+        state
+    }
+
+    #[test]
+    fn equal_weights_reproduce_delaunay_triangulation() {
+        let points = sample_points(PointGenerator::Uniform, 40, 7);
+        let plain = triangulate(points.clone(), Algorithm::EdgeAdvancing);
+
+        let mut weighted = TriangulationState {
+            weights: Some(vec![25.0; points.len()]),
+            points,
+            ..TriangulationState::default()
+        };
+        power_triangulation(&mut weighted);
+
+        assert!(weighted.is_triangulation_completed());
+        assert_eq!(weighted.triangles, plain.triangles);
+    }
+
+    #[test]
+    fn set_weight_pads_weights_and_resets_triangulation() {
+        let mut state = triangulate(sample_points(PointGenerator::Uniform, 5, 1), Algorithm::EdgeAdvancing);
+        assert!(state.is_triangulation_completed());
+
+        state.set_weight(3, 9.0);
+        assert_eq!(state.weights, Some(vec![0.0, 0.0, 0.0, 9.0, 0.0]));
+        assert!(!state.is_triangulation_initialized());
+
+        state.set_weight(3, 0.0);
+        assert_eq!(state.weights, None);
+    }
+
+    #[test]
+    fn step_stores_the_retired_edge() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 20, 3),
+            ..TriangulationState::default()
+        };
+        init_triangulation(&mut state);
+
+        let initial_edge = *state.edges_queue.front().unwrap();
+        step_triangulation(&mut state);
+        assert_eq!(state.current_edge, Some(initial_edge));
+
+        while !state.is_triangulation_completed() {
+            step_triangulation(&mut state);
+            if let Some(edge) = state.current_edge {
+                assert!(state.dead_edges.contains(&edge));
+                assert!(!state.alive_edges.contains(&edge));
+                let triangle = Polygon::from_poses([edge.0, edge.1, state.current_point.unwrap()]);
+                assert!(state.triangles.contains(&triangle));
+            }
+        }
+    }
+
+    /// Полукольцо: внешняя и внутренняя дуги радиусов 100 и 60 от 10 до 170 градусов.
+    /// Точки внутренней дуги, кроме концов, лежат строго внутри выпуклой оболочки.
+    fn crescent() -> Vec<Pos2> {
+        let arc = |radius: f32| {
+            (0..13).map(move |i| {
+                let angle = (10.0 + 160.0 * i as f32 / 12.0).to_radians();
+                Pos2::new(radius * angle.cos(), radius * angle.sin())
+            })
+        };
+        arc(100.0).chain(arc(60.0)).collect()
+    }
+
+    /// Вершины рёбер без повторений.
+    fn edge_vertexes(edges: &[(usize, usize)]) -> HashSet<usize> {
+        edges.iter().flat_map(|&(start, end)| [start, end]).collect()
+    }
+
+    #[test]
+    fn alpha_shape_of_crescent_is_concave() {
+        let state = triangulate(crescent(), Algorithm::EdgeAdvancing);
+        let hull: HashSet<usize> = convex_hull(&state.points).into_iter().collect();
+        // середина внутренней дуги
+        let inner_middle = 13 + 6;
+        assert!(!hull.contains(&inner_middle));
+
+        let concave = edge_vertexes(&state.alpha_shape(40.0));
+        assert!(concave.contains(&inner_middle));
+        assert!(!concave.is_subset(&hull));
+
+        // при большом alpha альфа-форма совпадает с выпуклой оболочкой
+        assert_eq!(edge_vertexes(&state.alpha_shape(1e6)), hull);
+    }
+
+    #[test]
+    fn fill_produces_one_convex_polygon_per_triangle() {
+        let state = triangulate(sample_points(PointGenerator::Uniform, 30, 5), Algorithm::EdgeAdvancing);
+        let style = PolygonStyle::dead();
+        let shapes = state.triangle_fill_shapes(&TSTransform::IDENTITY, &style);
+
+        assert!(!state.triangles.is_empty());
+        assert_eq!(shapes.len(), state.triangles.len());
+        for shape in shapes {
+            let egui::Shape::Path(path) = shape else {
+                panic!("ожидался многоугольник, получено {shape:?}");
+            };
+            assert!(path.closed);
+            assert_eq!(path.points.len(), 3);
+            assert_eq!(path.fill, style.fill_color);
+        }
+    }
+
+    /// Минимальный угол сетки в градусах.
+    fn mesh_min_angle(state: &TriangulationState) -> f32 {
+        state
+            .triangles
+            .iter()
+            .map(|triangle| triangle.min_angle(&state.points).to_degrees())
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn refine_raises_minimum_angle() {
+        // квадрат с точкой у самой стороны: треугольник у этой стороны очень вытянут
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(100.0, 0.0),
+            Pos2::new(100.0, 100.0),
+            Pos2::new(0.0, 100.0),
+            Pos2::new(50.0, 3.0),
+        ];
+        let skinny = triangulate(points.clone(), Algorithm::EdgeAdvancing);
+        let before = mesh_min_angle(&skinny);
+
+        let mut refined = TriangulationState {
+            points,
+            ..TriangulationState::default()
+        };
+        refined.refine(20.0, f32::INFINITY);
+        let after = mesh_min_angle(&refined);
+        assert!(before < 5.0);
+        assert!(refined.is_triangulation_completed());
+        assert!(after >= 20.0, "минимальный угол после улучшения {after}");
+    }
+
+    #[test]
+    fn points_csv_round_trips_exactly() {
+        let state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 50, 3),
+            ..TriangulationState::default()
+        };
+
+        let csv = state.points_to_csv(true);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("x,y"));
+        let parsed: Vec<Pos2> = lines
+            .map(|line| {
+                let (x, y) = line.split_once(',').unwrap();
+                Pos2::new(x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(parsed, state.points);
+
+        // без заголовка — только строки с точками
+        assert_eq!(state.points_to_csv(false).lines().count(), state.points.len());
+    }
+
+    #[test]
+    fn boundary_of_square_mesh_is_one_ordered_loop() {
+        // квадрат с точкой в центре: четыре треугольника, внутренние рёбра в границу не входят
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(0.0, 10.0),
+            Pos2::new(5.0, 5.0),
+        ];
+        let state = triangulate(points, Algorithm::EdgeAdvancing);
+        assert_eq!(state.triangles.len(), 4);
+
+        // обход против часовой стрелки на экране (ось y вниз) от наименьшего индекса
+        assert_eq!(state.boundary_loops(), vec![vec![0, 3, 2, 1]]);
+    }
+
+    #[test]
+    fn merge_replaces_close_points_with_midpoint() {
+        let mut state = TriangulationState {
+            points: vec![Pos2::new(10.0, 10.0), Pos2::new(50.0, 50.0), Pos2::new(10.5, 10.0)],
+            ..TriangulationState::default()
+        };
+
+        assert_eq!(state.merge_close_points(1.0), 1);
+        assert_eq!(state.points, vec![Pos2::new(10.25, 10.0), Pos2::new(50.0, 50.0)]);
+        // повторное объединение ничего не меняет
+        assert_eq!(state.merge_close_points(1.0), 0);
+    }
+
+    /// Треугольники, заданные координатами вершин (не зависят от порядка точек).
+    fn triangles_by_coordinates(state: &TriangulationState) -> BTreeSet<[(u32, u32); 3]> {
+        state
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let mut vertexes = triangle.vertexes(&state.points).map(|p| (p.x.to_bits(), p.y.to_bits()));
+                vertexes.sort();
+                vertexes
+            })
+            .collect()
+    }
 
-    let x = numerator_x / denominator;
-    let y = numerator_y / denominator;
+    #[test]
+    fn triangulation_of_symmetric_grid_is_deterministic() {
+        // на решётке все четвёрки соседних точек лежат на одной окружности
+        let grid: Vec<Pos2> = (0..36)
+            .map(|i| Pos2::new((i % 6) as f32 * 10.0, (i / 6) as f32 * 10.0))
+            .collect();
+        let expected = triangles_by_coordinates(&triangulate(grid.clone(), Algorithm::EdgeAdvancing));
+        assert_eq!(expected.len(), 50);
 
-    Some(Pos2::new(x, y))
+        for _ in 0..3 {
+            let repeated = triangulate(grid.clone(), Algorithm::EdgeAdvancing);
+            assert_eq!(triangles_by_coordinates(&repeated), expected);
+        }
+
+        // перестановка точек (7 и 36 взаимно просты)
+        let shuffled: Vec<Pos2> = (0..36).map(|i| grid[i * 7 % 36]).collect();
+        let shuffled = triangulate(shuffled, Algorithm::EdgeAdvancing);
+        assert_eq!(triangles_by_coordinates(&shuffled), expected);
+    }
+
+    #[test]
+    fn circumcircle_of_right_triangle_and_collinear_points() {
+        // центр описанной окружности прямоугольного треугольника — середина гипотенузы
+        let (center, radius) = circumcircle(Pos2::new(0.0, 0.0), Pos2::new(6.0, 0.0), Pos2::new(0.0, 8.0)).unwrap();
+        assert_eq!(center, Pos2::new(3.0, 4.0));
+        assert_eq!(radius, 5.0);
+
+        // порядок вершин не важен
+        let (center, radius) = circumcircle(Pos2::new(0.0, 8.0), Pos2::new(6.0, 0.0), Pos2::new(0.0, 0.0)).unwrap();
+        assert_eq!(center, Pos2::new(3.0, 4.0));
+        assert_eq!(radius, 5.0);
+
+        assert_eq!(circumcircle(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0), Pos2::new(3.0, 3.0)), None);
+        assert_eq!(circumcircle(Pos2::new(1.0, 1.0), Pos2::new(1.0, 1.0), Pos2::new(5.0, 2.0)), None);
+    }
+
+    #[test]
+    fn crossing_edges_of_overlapping_triangles() {
+        let state = triangulate(sample_points(PointGenerator::Uniform, 40, 11), Algorithm::EdgeAdvancing);
+        assert!(state.crossing_edges().is_empty());
+
+        // обе диагонали квадрата: пересекаются только они
+        let mut state = TriangulationState {
+            points: vec![
+                Pos2::new(0.0, 0.0),
+                Pos2::new(10.0, 0.0),
+                Pos2::new(10.0, 10.0),
+                Pos2::new(0.0, 10.0),
+            ],
+            ..TriangulationState::default()
+        };
+        state.triangles = [Polygon::from_poses([0, 1, 2]), Polygon::from_poses([1, 2, 3])].into();
+        assert_eq!(state.crossing_edges(), vec![((0, 2), (1, 3))]);
+    }
+
+    #[test]
+    fn complete_triangulation_requires_initialization() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 10, 2),
+            ..TriangulationState::default()
+        };
+        complete_triangulation(&mut state);
+        assert!(!state.is_triangulation_initialized());
+        assert!(state.triangles.is_empty());
+
+        init_triangulation(&mut state);
+        complete_triangulation(&mut state);
+        assert!(state.is_triangulation_completed());
+        assert!(state.delaunay_violations().is_empty());
+    }
+
+    #[test]
+    fn complete_triangulation_of_collinear_points_terminates() {
+        let mut state = TriangulationState {
+            points: (0..10).map(|i| Pos2::new(i as f32 * 10.0, i as f32 * 5.0)).collect(),
+            ..TriangulationState::default()
+        };
+        init_triangulation(&mut state);
+        complete_triangulation(&mut state);
+        assert!(state.triangles.is_empty());
+    }
+
+    #[test]
+    fn convex_hull_of_degenerate_inputs() {
+        assert_eq!(convex_hull(&[]), Vec::<usize>::new());
+        assert_eq!(convex_hull(&[Pos2::new(1.0, 1.0)]), vec![0]);
+        assert_eq!(convex_hull(&[Pos2::new(5.0, 1.0), Pos2::new(1.0, 1.0)]), vec![1, 0]);
+
+        // точки на одной прямой: остаются только крайние
+        let collinear = [Pos2::new(2.0, 2.0), Pos2::new(0.0, 0.0), Pos2::new(3.0, 3.0), Pos2::new(1.0, 1.0)];
+        assert_eq!(convex_hull(&collinear), vec![1, 2]);
+    }
+
+    #[test]
+    fn convex_hull_skips_duplicates_and_interior_points() {
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(5.0, 5.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(0.0, 10.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(5.0, 0.0),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 4);
+        assert_eq!(hull.iter().filter(|&&i| points[i] == Pos2::new(10.0, 0.0)).count(), 1);
+        assert!(!hull.contains(&2) && !hull.contains(&6));
+    }
+
+    #[test]
+    fn convex_hull_is_counter_clockwise_on_screen() {
+        let points = sample_points(PointGenerator::Uniform, 100, 9);
+        let hull = convex_hull(&points);
+        assert!(hull.len() >= 3);
+        for i in 0..hull.len() {
+            let [a, b, c] = [0, 1, 2].map(|k| points[hull[(i + k) % hull.len()]]);
+            // на экране (ось y вниз) обход против часовой стрелки — всегда "левые" повороты
+            assert!(orient2d(a, b, c) < 0.0);
+        }
+        for (i, point) in points.iter().enumerate() {
+            if !hull.contains(&i) {
+                for k in 0..hull.len() {
+                    let (a, b) = (points[hull[k]], points[hull[(k + 1) % hull.len()]]);
+                    assert!(orient2d(a, b, *point) <= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_diagonal_violates_empty_circle() {
+        // "воздушный змей": правильная диагональ — короткая 1-3
+        let mut state = TriangulationState {
+            points: vec![
+                Pos2::new(0.0, 0.0),
+                Pos2::new(10.0, -2.0),
+                Pos2::new(20.0, 0.0),
+                Pos2::new(10.0, 2.0),
+            ],
+            ..TriangulationState::default()
+        };
+        state.triangles = [Polygon::from_poses([0, 1, 3]), Polygon::from_poses([1, 2, 3])].into();
+        assert!(state.delaunay_violations().is_empty());
+
+        // длинная диагональ 0-2: каждая из противолежащих вершин внутри чужой окружности
+        state.triangles = [Polygon::from_poses([0, 1, 2]), Polygon::from_poses([0, 2, 3])].into();
+        assert_eq!(
+            state.delaunay_violations(),
+            vec![(Polygon::from_poses([0, 1, 2]), 3), (Polygon::from_poses([0, 2, 3]), 1)]
+        );
+    }
+
+    #[test]
+    fn delaunay_triangulation_has_no_violations() {
+        for generator in [PointGenerator::Uniform, PointGenerator::Grid, PointGenerator::Circle] {
+            let state = triangulate(sample_points(generator, 200, 4), Algorithm::EdgeAdvancing);
+            assert!(!state.triangles.is_empty());
+            assert!(state.delaunay_violations().is_empty(), "{generator:?}");
+        }
+    }
+
+    #[test]
+    fn voronoi_cell_of_square_center_is_diamond() {
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+            Pos2::new(0.0, 10.0),
+            Pos2::new(5.0, 5.0),
+        ];
+        let state = triangulate(points, Algorithm::EdgeAdvancing);
+        let diagram = VoronoiDiagram::from_triangulation(&state).unwrap();
+
+        // центры описанных окружностей — середины сторон квадрата
+        let mut cell = diagram.cells[4].clone();
+        cell.sort_by(|u, v| u.x.total_cmp(&v.x).then(u.y.total_cmp(&v.y)));
+        assert_eq!(
+            cell,
+            vec![Pos2::new(0.0, 5.0), Pos2::new(5.0, 0.0), Pos2::new(5.0, 10.0), Pos2::new(10.0, 5.0)]
+        );
+        assert!(diagram.cells[..4].iter().all(Vec::is_empty));
+        // порядок рёбер не определён
+        let edges = build_voronoi(&state);
+        assert_eq!(edges.len(), diagram.edges.len());
+        assert!(edges.iter().all(|edge| diagram.edges.contains(edge)));
+
+        assert!(build_voronoi(&TriangulationState::default()).is_empty());
+    }
+
+    #[test]
+    fn grid_pruned_conjugate_search_matches_brute_force() {
+        for generator in [PointGenerator::Uniform, PointGenerator::Gaussian, PointGenerator::Grid] {
+            let points = sample_points(generator, 300, 8);
+            let index = PointGrid::new(&points);
+            for i in (0..points.len()).step_by(7) {
+                for j in (0..points.len()).step_by(11) {
+                    if points[i] == points[j] {
+                        continue;
+                    }
+                    let edge = Edge::new(i, j);
+                    assert_eq!(
+                        find_right_conjugate_point(&points, None, edge, Some(&index)),
+                        find_right_conjugate_point(&points, None, edge, None),
+                        "{generator:?} {edge:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "замер времени: cargo test --release -- --ignored --nocapture"]
+    fn point_index_is_faster_than_brute_force() {
+        let points = sample_points(PointGenerator::Uniform, 10_000, 1);
+        let mut timings = Vec::new();
+        let mut results = Vec::new();
+        for use_index in [true, false] {
+            let mut state = TriangulationState {
+                points: points.clone(),
+                ..TriangulationState::default()
+            };
+            init_triangulation(&mut state);
+            if !use_index {
+                state.point_index = None;
+            }
+            let start = std::time::Instant::now();
+            complete_triangulation(&mut state);
+            timings.push(start.elapsed());
+            results.push(state.triangles);
+        }
+        println!("с индексом: {:?}, перебором: {:?}", timings[0], timings[1]);
+        assert_eq!(results[0], results[1]);
+        assert!(timings[0] < timings[1], "{timings:?}");
+    }
+
+    #[test]
+    fn sanitize_rebuilds_point_index_of_restored_triangulation() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 50, 3),
+            ..TriangulationState::default()
+        };
+        init_triangulation(&mut state);
+        for _ in 0..10 {
+            step_triangulation(&mut state);
+        }
+
+        // индекс не сериализуется
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored: TriangulationState = serde_json::from_str(&json).unwrap();
+        assert!(restored.point_index.is_none());
+        assert!(!restored.sanitize());
+        assert_eq!(restored.point_index.as_ref().map(PointGrid::point_count), Some(50));
+
+        complete_triangulation(&mut state);
+        complete_triangulation(&mut restored);
+        assert_eq!(restored.triangles, state.triangles);
+    }
+
+    #[test]
+    fn lattice_triangulations_of_both_algorithms_match() {
+        for count in [16, 49, 100] {
+            // все четвёрки соседних точек решётки лежат на одной окружности
+            let points = sample_points(PointGenerator::Grid, count, 1);
+            let edge_advancing = triangulate(points.clone(), Algorithm::EdgeAdvancing);
+            let bowyer_watson = triangulate(points, Algorithm::BowyerWatson);
+            assert!(edge_advancing.delaunay_violations().is_empty());
+            assert!(bowyer_watson.delaunay_violations().is_empty());
+            assert_eq!(edge_advancing.triangles, bowyer_watson.triangles, "{count}");
+        }
+    }
+
+    #[test]
+    fn power_center_with_equal_weights_is_circumcenter() {
+        let [a, b, c] = [Pos2::new(0.0, 0.0), Pos2::new(6.0, 0.0), Pos2::new(0.0, 8.0)];
+        assert_eq!(calculate_power_center([a, b, c], [0.0; 3]), Some(Pos2::new(3.0, 4.0)));
+        assert_eq!(calculate_power_center([a, b, c], [7.0; 3]), Some(Pos2::new(3.0, 4.0)));
+
+        // больший вес точки отодвигает центр от неё: степенные расстояния остаются равными
+        let weights = [0.0, 12.0, 0.0];
+        let center = calculate_power_center([a, b, c], weights).unwrap();
+        assert_eq!(center, Pos2::new(2.0, 4.0));
+        let powers: Vec<f32> = [a, b, c].iter().zip(weights).map(|(p, w)| (*p - center).length_sq() - w).collect();
+        assert_eq!(powers, vec![20.0; 3]);
+
+        assert_eq!(calculate_power_center([a, a, c], [0.0; 3]), None);
+    }
+
+    #[test]
+    fn unequal_weights_change_mesh_without_violations() {
+        // "воздушный змей": без весов строится короткая диагональ 1-3
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, -2.0),
+            Pos2::new(20.0, 0.0),
+            Pos2::new(10.0, 2.0),
+        ];
+        let plain = triangulate(points.clone(), Algorithm::EdgeAdvancing);
+        assert_eq!(plain.triangles, [Polygon::from_poses([0, 1, 3]), Polygon::from_poses([1, 2, 3])].into());
+
+        // тяжёлые концы длинной диагонали делают выгоднее её
+        let mut weighted = TriangulationState {
+            points,
+            ..TriangulationState::default()
+        };
+        weighted.set_weight(0, 100.0);
+        weighted.set_weight(2, 100.0);
+        power_triangulation(&mut weighted);
+        assert_eq!(weighted.triangles, [Polygon::from_poses([0, 1, 2]), Polygon::from_poses([0, 2, 3])].into());
+        assert!(weighted.delaunay_violations().is_empty());
+
+        // та же сетка без весов нарушает обычное свойство пустой окружности
+        weighted.weights = None;
+        assert_eq!(weighted.delaunay_violations().len(), 2);
+    }
+
+    #[test]
+    fn random_weights_give_no_power_violations() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 60, 12),
+            ..TriangulationState::default()
+        };
+        for i in 0..state.points.len() {
+            state.set_weight(i, (i * 7919 % 13) as f32 * 150.0);
+        }
+        power_triangulation(&mut state);
+        assert!(!state.triangles.is_empty());
+        assert!(state.delaunay_violations().is_empty());
+    }
+
+    #[test]
+    fn weights_switch_to_edge_advancing() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 10, 1),
+            algorithm: Algorithm::BowyerWatson,
+            ..TriangulationState::default()
+        };
+        state.set_weight(3, 50.0);
+        assert_eq!(state.algorithm, Algorithm::EdgeAdvancing);
+        // при заданных весах Бойер — Уотсон не выбирается
+        state.set_algorithm(Algorithm::BowyerWatson);
+        assert_eq!(state.algorithm, Algorithm::EdgeAdvancing);
+
+        state.set_weight(3, 0.0);
+        state.set_algorithm(Algorithm::BowyerWatson);
+        assert_eq!(state.algorithm, Algorithm::BowyerWatson);
+    }
+
+    #[test]
+    fn cavity_skips_super_vertices_after_adding_point() {
+        let mut state = TriangulationState {
+            points: sample_points(PointGenerator::Uniform, 6, 3),
+            algorithm: Algorithm::BowyerWatson,
+            ..TriangulationState::default()
+        };
+        // шаг, на котором граница полости соединяет точку с вершиной супертреугольника 6
+        init_triangulation(&mut state);
+        let touches_super_vertex = |state: &TriangulationState| {
+            state.bowyer_watson.cavity.iter().any(|&(start, end)| start < 6 && end == 6)
+        };
+        while !touches_super_vertex(&state) {
+            assert!(!state.is_triangulation_completed());
+            step_triangulation(&mut state);
+        }
+        let visible: Vec<_> = state.visible_cavity().collect();
+        assert!(visible.iter().all(|&(start, end)| start < 6 && end < 6));
+
+        // точка, добавленная посреди построения, получает индекс первой вершины супертреугольника
+        state.points.push(Pos2::new(500.0, 500.0));
+        assert_eq!(state.visible_cavity().collect::<Vec<_>>(), visible);
+    }
+
+    #[test]
+    fn sanitize_drops_non_finite_points_and_weights() {
+        let mut state = TriangulationState {
+            points: vec![
+                Pos2::new(0.0, 0.0),
+                Pos2::new(f32::NAN, 1.0),
+                Pos2::new(10.0, 0.0),
+                Pos2::new(5.0, f32::INFINITY),
+                Pos2::new(5.0, 8.0),
+            ],
+            weights: Some(vec![1.0, 2.0, f32::NAN, 4.0, 5.0]),
+            ..TriangulationState::default()
+        };
+        assert!(state.sanitize());
+        assert_eq!(state.points, vec![Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), Pos2::new(5.0, 8.0)]);
+        assert_eq!(state.weights, Some(vec![1.0, 0.0, 5.0]));
+
+        // после очистки оболочка и триангуляция строятся как обычно
+        assert_eq!(convex_hull(&state.points).len(), 3);
+        power_triangulation(&mut state);
+        assert_eq!(state.triangles.len(), 1);
+        assert!(!state.sanitize());
+    }
 }
@@ -1,4 +1,22 @@
-use crate::app::AthenianApp;
+use crate::app::{
+    AthenianApp, Instrument,
+    logic::{
+        generators::PointGenerator,
+        polygon::{PolygonStyle, StyleSettings},
+        triangulation::{Algorithm, TriangulationState},
+    },
+};
+
+/// Время показа всплывающего сообщения (в секундах).
+const TOAST_DURATION: f64 = 3.0;
+
+/// Временное всплывающее сообщение.
+pub struct Toast {
+    /// Текст сообщения.
+    pub message: String,
+    /// Момент первого показа (заполняется при отрисовке).
+    pub shown_at: Option<f64>,
+}
 
 // --------------------------------------------------
 // Построение UI приложения
@@ -7,19 +25,45 @@ use crate::app::AthenianApp;
 impl eframe::App for AthenianApp {
     /// Главный цикл UI.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // без анимации полагаемся на перерисовку egui по событиям ввода
+        if self.is_animation_active() {
+            self.advance_animation(ctx);
+        }
+
         self.show_top_panel(ctx);
         self.show_left_panel(ctx);
         self.show_bottom_panel(ctx);
         self.show_cental_panel(ctx);
+        self.show_style_window(ctx);
+        self.show_generator_window(ctx);
+        self.show_toast(ctx);
+    }
+
+    /// Сохранение состояния между запусками.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
     }
 }
 
 impl AthenianApp {
     /// Показать верхную панель приложения.
-    fn show_top_panel(&self, ctx: &egui::Context) {
+    fn show_top_panel(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.menu_button("File", |ui| {
+                    if ui.button("Open project...").clicked() {
+                        self.open_project();
+                    }
+                    if ui.button("Save project...").clicked() {
+                        self.save_project();
+                    }
+                    ui.separator();
+                    if ui.button("Export points (CSV)").clicked() {
+                        self.export_points_csv();
+                    }
+                    if ui.button("Export SVG").clicked() {
+                        self.export_svg();
+                    }
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
@@ -38,17 +82,144 @@ impl AthenianApp {
                         self.clear_canvas();
                     }
 
+                    if ui.button("Сбросить вид").clicked() {
+                        self.reset_view();
+                    }
+
+                    if ui.button("Сгенерировать точки...").clicked() {
+                        self.show_generator_window = true;
+                    }
+
+                    if ui.button("Объединить близкие точки").clicked() {
+                        self.merge_close_points();
+                    }
+
                     ui.separator();
 
                     ui.label("Инструменты:");
 
-                    if ui.button("Выполнить 1 шаг").clicked() {
-                        self.do_triangulation_step();
+                    for instrument in [
+                        Instrument::AddPoint,
+                        Instrument::MoveVertex,
+                        Instrument::InsertVertex,
+                        Instrument::DeleteVertex,
+                        Instrument::SetWeight,
+                        Instrument::Circumcircle,
+                    ] {
+                        if ui
+                            .selectable_label(self.instrument == instrument, instrument.to_string())
+                            .clicked()
+                        {
+                            self.set_instrument(instrument);
+                        }
+                    }
+
+                    if self.instrument == Instrument::SetWeight {
+                        ui.horizontal(|ui| {
+                            ui.label("Вес:");
+                            ui.add(egui::DragValue::new(&mut self.point_weight).range(0.0..=10000.0).speed(10.0));
+                        });
+                    }
+
+                    ui.separator();
+
+                    ui.label("Триангуляция:");
+
+                    let mut algorithm = self.state.algorithm;
+                    egui::ComboBox::from_label("Алгоритм")
+                        .selected_text(algorithm.to_string())
+                        .show_ui(ui, |ui| {
+                            for variant in [Algorithm::EdgeAdvancing, Algorithm::BowyerWatson] {
+                                let enabled = self.state.weights.is_none()
+                                    || TriangulationState::supports_weights(variant);
+                                ui.add_enabled_ui(enabled, |ui| {
+                                    ui.selectable_value(&mut algorithm, variant, variant.to_string())
+                                        .on_disabled_hover_text("Не поддерживает веса точек");
+                                });
+                            }
+                        });
+                    self.state.set_algorithm(algorithm);
+
+                    if ui
+                        .add_enabled(!self.state.is_triangulation_initialized(), egui::Button::new("Начать"))
+                        .clicked()
+                    {
+                        self.init_triangulation();
                     }
 
+                    ui.horizontal(|ui| {
+                        if ui.button("Выполнить шагов:").clicked() {
+                            self.do_triangulation_steps();
+                        }
+                        ui.add(egui::DragValue::new(&mut self.steps_per_click).range(1..=1000));
+                    });
+
+                    ui.horizontal(|ui| {
+                        if self.running {
+                            if ui.button("Пауза").clicked() {
+                                self.pause_animation();
+                            }
+                        } else if ui.button("Запустить").clicked() {
+                            self.run_animation();
+                        }
+                        ui.add(egui::Slider::new(&mut self.steps_per_second, 0.5..=60.0).text("шагов/с"));
+                    });
+
                     if ui.button("Завершить полностью").clicked() {
                         self.do_full_triangulation();
                     }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Улучшить сетку").clicked() {
+                            self.refine_mesh();
+                        }
+                        ui.add(
+                            egui::DragValue::new(&mut self.refine_min_angle)
+                                .range(0.0..=30.0)
+                                .suffix("°"),
+                        );
+                    });
+
+                    if ui.button("Сбросить триангуляцию").clicked() {
+                        self.reset_triangulation();
+                    }
+
+                    ui.separator();
+
+                    ui.label("Настройки:");
+
+                    if ui.button("Стили...").clicked() {
+                        self.show_style_window = true;
+                    }
+
+                    ui.checkbox(&mut self.fill_triangles, "Заливка треугольников");
+                    ui.checkbox(&mut self.show_violations, "Показать нарушения Делоне");
+                    ui.checkbox(&mut self.show_voronoi, "Диаграмма Вороного");
+                    ui.checkbox(&mut self.show_hull, "Выпуклая оболочка");
+
+                    let mut show_alpha_shape = self.alpha_shape.is_some();
+                    if ui.checkbox(&mut show_alpha_shape, "Альфа-форма").changed() {
+                        self.alpha_shape = if show_alpha_shape { Some(50.0) } else { None };
+                    }
+                    if let Some(alpha) = &mut self.alpha_shape {
+                        ui.add(egui::Slider::new(alpha, 1.0..=1000.0).logarithmic(true).text("α"));
+                    }
+
+                    let mut limited = self.max_points.is_some();
+                    if ui.checkbox(&mut limited, "Ограничить число точек").changed() {
+                        self.max_points = if limited { Some(100) } else { None };
+                    }
+                    if let Some(max_points) = &mut self.max_points {
+                        ui.add(egui::DragValue::new(max_points).range(3..=10000));
+                    }
+
+                    let mut snapping = self.grid_size.is_some();
+                    if ui.checkbox(&mut snapping, "Привязка к сетке").changed() {
+                        self.grid_size = if snapping { Some(20.0) } else { None };
+                    }
+                    if let Some(grid_size) = &mut self.grid_size {
+                        ui.add(egui::Slider::new(grid_size, 5.0..=100.0).text("шаг (+/-)"));
+                    }
                 });
             });
     }
@@ -61,7 +232,19 @@ impl AthenianApp {
 
                 ui.separator();
 
+                let stats = self.scene_stats();
+                ui.label(format!(
+                    "точек: {}, треугольников: {}, площадь: {:.1}",
+                    stats.point_count, stats.triangle_count, stats.total_area
+                ));
+
+                ui.separator();
+
                 ui.label(format!("размер холста: {:.1} x {:.1}", self.painter_width, self.painter_height));
+
+                ui.separator();
+
+                ui.label(format!("масштаб: {:.2}", self.view.scaling));
             });
         });
     }
@@ -78,4 +261,131 @@ impl AthenianApp {
                 });
         });
     }
+
+    /// Показать окно настройки стилей рисования.
+    fn show_style_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_style_window;
+        egui::Window::new("Стили")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.collapsing("Стандартный", |ui| {
+                    Self::style_editor(ui, &mut self.styles.standard);
+                });
+                ui.collapsing("Выбранный", |ui| {
+                    Self::style_editor(ui, &mut self.styles.selected);
+                });
+                if ui.button("Сбросить").clicked() {
+                    self.styles = StyleSettings::default();
+                }
+            });
+        self.show_style_window = open;
+    }
+
+    /// Редактор одного стиля.
+    fn style_editor(ui: &mut egui::Ui, style: &mut PolygonStyle) {
+        egui::Grid::new(ui.next_auto_id()).num_columns(3).show(ui, |ui| {
+            ui.label("Вершины");
+            ui.color_edit_button_srgba(&mut style.vertex_color);
+            ui.add(egui::DragValue::new(&mut style.vertex_radius).range(0.0..=30.0));
+            ui.end_row();
+
+            ui.label("Рёбра");
+            ui.color_edit_button_srgba(&mut style.edge_color);
+            ui.add(egui::DragValue::new(&mut style.edge_width).range(0.0..=30.0));
+            ui.end_row();
+
+            ui.label("Пересечения");
+            ui.color_edit_button_srgba(&mut style.intersection_color);
+            ui.add(egui::DragValue::new(&mut style.intersection_radius).range(0.0..=30.0));
+            ui.end_row();
+
+            ui.label("Стрелки");
+            ui.color_edit_button_srgba(&mut style.arrow_color);
+            ui.add(egui::DragValue::new(&mut style.arrow_width).range(0.0..=30.0));
+            ui.end_row();
+
+            ui.label("Заливка");
+            ui.color_edit_button_srgba(&mut style.fill_color);
+            ui.end_row();
+        });
+    }
+
+    /// Показать окно генерации точек.
+    fn show_generator_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_generator_window;
+        egui::Window::new("Генерация точек")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let settings = &mut self.generator;
+                egui::ComboBox::from_label("Расстановка")
+                    .selected_text(settings.generator.to_string())
+                    .show_ui(ui, |ui| {
+                        for generator in [
+                            PointGenerator::Uniform,
+                            PointGenerator::Grid,
+                            PointGenerator::Circle,
+                            PointGenerator::Gaussian,
+                        ] {
+                            ui.selectable_value(&mut settings.generator, generator, generator.to_string());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.label("Точек:");
+                    ui.add(egui::DragValue::new(&mut settings.count).range(1..=10000));
+                });
+                if settings.generator == PointGenerator::Gaussian {
+                    ui.horizontal(|ui| {
+                        ui.label("Кластеров:");
+                        ui.add(egui::DragValue::new(&mut settings.clusters).range(1..=50));
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Зерно:");
+                    ui.add(egui::DragValue::new(&mut settings.seed));
+                });
+                ui.checkbox(&mut settings.replace, "Заменить существующие точки");
+
+                if ui.button("Сгенерировать").clicked() {
+                    self.generate_points();
+                }
+            });
+        self.show_generator_window = open;
+    }
+
+    /// Показать всплывающее предупреждение.
+    pub fn show_warning(&mut self, message: impl Into<String>) {
+        self.toast = Some(Toast {
+            message: message.into(),
+            shown_at: None,
+        });
+    }
+
+    /// Показать текущее всплывающее сообщение, если оно есть.
+    fn show_toast(&mut self, ctx: &egui::Context) {
+        let Some(toast) = &mut self.toast else {
+            return;
+        };
+
+        let now = ctx.input(|i| i.time);
+        let shown_at = *toast.shown_at.get_or_insert(now);
+        if now - shown_at > TOAST_DURATION {
+            self.toast = None;
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toast"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -40.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.colored_label(egui::Color32::DARK_RED, &toast.message);
+                });
+            });
+
+        // перерисовать, чтобы сообщение исчезло вовремя
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+            TOAST_DURATION - (now - shown_at),
+        ));
+    }
 }